@@ -5,15 +5,16 @@ use std::{
 };
 
 use crate::error::Result;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use pinitd_common::{
-    CONTROL_SOCKET_ADDRESS, PACKAGE_NAME, ServiceStatus,
+    CONTROL_SOCKET_ADDRESS, PACKAGE_NAME, ServiceStatus, UID, WorkerIdentity,
     android::fetch_package_path,
     error::Error,
     protocol::{
-        CLICommand, CLIResponse,
+        CLICommand, CLIResponse, WorkerSummary,
         writable::{ProtocolRead, ProtocolWrite},
     },
+    unit_config::ServiceConfig,
 };
 use tokio::{io::AsyncWriteExt, net::TcpStream, time::timeout};
 
@@ -24,6 +25,24 @@ mod error;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Output format for `list`/`status`/`config` responses
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table, global = true)]
+    format: OutputFormat,
+}
+
+/// Rendering chosen for `list`/`status`/`config` responses, so pinitd can be scripted
+/// (`pinitd list --format json | jq ...`) instead of only ever scraping human text
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// One record per line, tab-separated, no header - easy to pipe into cut/awk
+    Plain,
+    /// Aligned columns with a header, for interactive use (the default)
+    Table,
+    /// Newline-delimited JSON, one record per line
+    Json,
+    /// CSV with a header row
+    Csv,
 }
 
 #[derive(Parser, Debug)]
@@ -50,14 +69,51 @@ enum Commands {
     List,
     /// Request the daemon to shut down gracefully
     Shutdown,
+    /// List connected workers and their liveness state
+    Workers,
+    /// Stop a worker from accepting new work, without affecting what it's already hosting
+    PauseWorker {
+        uid: String,
+        se_info: Option<String>,
+    },
+    /// Undo a prior pause-worker/drain-worker
+    ResumeWorker {
+        uid: String,
+        se_info: Option<String>,
+    },
+    /// Stop a worker from accepting new work and shut it down once it's done with what it's
+    /// already hosting
+    DrainWorker {
+        uid: String,
+        se_info: Option<String>,
+    },
+    /// Show a service's captured stdout/stderr
+    Logs {
+        name: String,
+        /// Keep the connection open and print new lines as they arrive
+        #[arg(short, long)]
+        follow: bool,
+        /// Number of buffered lines to show before following
+        #[arg(short = 'n', long, default_value_t = 100)]
+        lines: usize,
+    },
 
     /// Start pinitd directly in shell domain, without vulnerability
     DebugManualStart,
 }
 
+fn parse_worker_identity(uid: String, se_info: Option<String>) -> Result<WorkerIdentity> {
+    let uid: UID = uid
+        .as_str()
+        .try_into()
+        .map_err(|err| Error::Unknown(format!("Invalid uid \"{uid}\": {err}")))?;
+    Ok(WorkerIdentity::new(uid, se_info))
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    let format = cli.format;
 
     let initd_command = match cli.command {
         Commands::Start { name } => CLICommand::Start(name),
@@ -71,6 +127,28 @@ async fn main() -> Result<()> {
         Commands::Config { name } => CLICommand::Config(name),
         Commands::List => CLICommand::List,
         Commands::Shutdown => CLICommand::Shutdown,
+        Commands::Workers => CLICommand::Workers,
+        Commands::PauseWorker { uid, se_info } => {
+            CLICommand::PauseWorker(parse_worker_identity(uid, se_info)?)
+        }
+        Commands::ResumeWorker { uid, se_info } => {
+            CLICommand::ResumeWorker(parse_worker_identity(uid, se_info)?)
+        }
+        Commands::DrainWorker { uid, se_info } => {
+            CLICommand::DrainWorker(parse_worker_identity(uid, se_info)?)
+        }
+        Commands::Logs {
+            name,
+            follow,
+            lines,
+        } => {
+            return stream_logs(CLICommand::Logs {
+                name,
+                follow,
+                lines,
+            })
+            .await;
+        }
         Commands::DebugManualStart => {
             return debug_manual_start().await;
         }
@@ -103,31 +181,65 @@ async fn main() -> Result<()> {
             exit_with_message(&format!("Error: {msg}"));
         }
         CLIResponse::Status(info) => {
-            print_status(&[info]);
+            print_statuses(&[info], format);
             Ok(())
         }
         CLIResponse::List(list) => {
-            if list.is_empty() {
+            if list.is_empty() && format == OutputFormat::Table {
                 println!("No services configured");
             } else {
-                print_status(&list);
+                print_statuses(&list, format);
             }
             Ok(())
         }
         CLIResponse::Config(config) => {
-            println!("Name: {}", config.name);
-            println!("Command: {}", config.command);
-            println!("Autostart: {}", config.autostart);
-            println!("Restart: {:?}", config.restart);
-            if let Some(nice_name) = config.nice_name {
-                println!("NiceName: {nice_name}");
-            }
+            print_config(&config, format);
             Ok(())
         }
         CLIResponse::ShuttingDown => {
             println!("Shutting down");
             Ok(())
         }
+        CLIResponse::Workers(workers) => {
+            if workers.is_empty() {
+                println!("No workers connected");
+            } else {
+                print_workers(&workers);
+            }
+            Ok(())
+        }
+        CLIResponse::LogChunk(line) => {
+            println!("{line}");
+            Ok(())
+        }
+        CLIResponse::StatusChange(delta) => {
+            println!("{}: {}", delta.name, delta.new_state);
+            Ok(())
+        }
+    }
+}
+
+/// `Logs` gets its own request path: unlike every other command, the server may write several
+/// `CLIResponse::LogChunk`s before the terminating `Success`/`Error`, so the connection is kept
+/// open across multiple reads instead of the usual single write-then-read.
+async fn stream_logs(command: CLICommand) -> Result<()> {
+    let mut stream = match TcpStream::connect(CONTROL_SOCKET_ADDRESS).await {
+        Ok(stream) => stream,
+        Err(_) => exit_with_message("Cannot find pinitd. Is it running?"),
+    };
+
+    command.write(&mut stream).await?;
+
+    loop {
+        match CLIResponse::read(&mut stream).await? {
+            CLIResponse::LogChunk(line) => println!("{line}"),
+            CLIResponse::Success(msg) => {
+                println!("{msg}");
+                return Ok(());
+            }
+            CLIResponse::Error(msg) => exit_with_message(&format!("Error: {msg}")),
+            _ => return Ok(()),
+        }
     }
 }
 
@@ -148,7 +260,16 @@ async fn debug_manual_start() -> Result<()> {
     Ok(())
 }
 
-fn print_status(statuses: &[ServiceStatus]) {
+fn print_statuses(statuses: &[ServiceStatus], format: OutputFormat) {
+    match format {
+        OutputFormat::Table => print_statuses_table(statuses),
+        OutputFormat::Plain => print_statuses_plain(statuses),
+        OutputFormat::Json => print_statuses_json(statuses),
+        OutputFormat::Csv => print_statuses_csv(statuses),
+    }
+}
+
+fn print_statuses_table(statuses: &[ServiceStatus]) {
     println!(
         " {:<41} {:<10} {:<20} {}",
         "NAME", "ENABLED", "STATE", "UID"
@@ -166,6 +287,97 @@ fn print_status(statuses: &[ServiceStatus]) {
     }
 }
 
+fn print_statuses_plain(statuses: &[ServiceStatus]) {
+    for info in statuses {
+        let uid: usize = info.uid.clone().into();
+        println!("{}\t{}\t{}\t{uid}", info.name, info.enabled, info.state);
+    }
+}
+
+fn print_statuses_json(statuses: &[ServiceStatus]) {
+    for info in statuses {
+        match serde_json::to_string(info) {
+            Ok(line) => println!("{line}"),
+            Err(err) => eprintln!("Failed to serialize \"{}\": {err}", info.name),
+        }
+    }
+}
+
+fn print_statuses_csv(statuses: &[ServiceStatus]) {
+    println!("name,enabled,state,uid");
+    for info in statuses {
+        let uid: usize = info.uid.clone().into();
+        println!(
+            "{},{},{},{uid}",
+            csv_field(&info.name),
+            info.enabled,
+            csv_field(&info.state.to_string()),
+        );
+    }
+}
+
+fn print_config(config: &ServiceConfig, format: OutputFormat) {
+    match format {
+        OutputFormat::Table | OutputFormat::Plain => {
+            println!("Name: {}", config.name);
+            println!("Command: {}", config.command);
+            println!("Autostart: {}", config.autostart);
+            println!("Restart: {:?}", config.restart);
+            if let Some(nice_name) = &config.nice_name {
+                println!("NiceName: {nice_name}");
+            }
+        }
+        OutputFormat::Json => match serde_json::to_string(config) {
+            Ok(line) => println!("{line}"),
+            Err(err) => eprintln!("Failed to serialize \"{}\": {err}", config.name),
+        },
+        OutputFormat::Csv => {
+            println!("field,value");
+            println!("name,{}", csv_field(&config.name));
+            println!("command,{}", csv_field(&config.command.to_string()));
+            println!("autostart,{}", config.autostart);
+            println!("restart,{}", csv_field(&format!("{:?}", config.restart)));
+            if let Some(nice_name) = &config.nice_name {
+                println!("nice_name,{}", csv_field(nice_name));
+            }
+        }
+    }
+}
+
+/// Quotes `field` per RFC 4180 if it contains a comma, quote, or newline; otherwise returns it
+/// unchanged
+fn csv_field(field: &str) -> String {
+    if field.contains(['"', ',', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn print_workers(workers: &[WorkerSummary]) {
+    println!(
+        " {:<40} {:<8} {:<8} {:<12} {}",
+        "UID/SE_INFO", "PID", "LIVENESS", "LAST SEEN", "SERVICES"
+    );
+    println!("{}", "-".repeat(80));
+    for worker in workers {
+        let uid: usize = worker.identity.uid.clone().into();
+
+        println!(
+            " {:<40} {:<8} {:<8} {:<12} {}",
+            format!("{uid}/{}", worker.identity.se_info),
+            worker.pid,
+            format!("{:?}", worker.liveness),
+            format!("{}s ago", worker.last_seen_secs_ago),
+            if worker.services.is_empty() {
+                "-".to_string()
+            } else {
+                worker.services.join(", ")
+            }
+        );
+    }
+}
+
 fn exit_with_message(message: &str) -> ! {
     eprintln!("{message}");
     process::exit(1);
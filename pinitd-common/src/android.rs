@@ -1,48 +1,78 @@
-use crate::error::{Error, Result};
+use crate::{
+    error::{Error, Result},
+    shell::ShellCommand,
+};
 use std::{process::Stdio, time::Duration};
 use tokio::{process::Command, time::timeout};
 
+/// logcat truncates (rather than wraps) entries past this many bytes, so lines longer than this
+/// are cut down before being handed to `log` rather than silently dropped by the platform
+const LOGCAT_LINE_LIMIT_BYTES: usize = 4000;
+
 pub async fn fetch_package_path(package: &str) -> Result<String> {
-    let child = Command::new("pm")
-        .args(&["path", package])
-        // TODO: Auto pipe output to Android log?
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .kill_on_drop(true)
-        .spawn()?;
-
-    let output = match timeout(Duration::from_millis(500), child.wait_with_output()).await {
-        Ok(Ok(output)) => {
-            if !output.status.success() {
-                Err(Error::PackageLookup(format!(
-                    "Could not find package {package}"
-                )))
-            } else {
-                Ok(output)
-            }
-        }
-        Ok(Err(_)) => Err(Error::PackageLookup(format!(
-            "Could not find package {package}"
-        ))),
-        Err(_) => Err(Error::PackageLookup(format!(
-            "Could not find package {package}"
-        ))),
-    }?;
-
-    let stdout = String::from_utf8(output.stdout).ok();
-
-    if let Some(stdout) = stdout {
-        let package_path = stdout.trim_start_matches("package:").trim();
-        if !package_path.starts_with("/data/app") {
-            return Err(Error::PackageLookup(format!(
-                "Found invalid package path for package {package}. Found {package_path}"
-            )));
+    let output = ShellCommand::new("pm")
+        .args(["path", package])
+        .timeout(Duration::from_millis(500))
+        .run_captured()
+        .await?;
+
+    let package_path = output.stdout.trim_start_matches("package:").trim();
+    if !package_path.starts_with("/data/app") {
+        return Err(Error::Unknown(format!(
+            "Found invalid package path for package {package}. Found {package_path}"
+        )));
+    }
+
+    Ok(package_path.into())
+}
+
+/// Reads an Android system property via `getprop`, returning an empty string if it's unset
+/// (matching `getprop`'s own behavior for an unknown key)
+pub async fn read_property(key: &str) -> Result<String> {
+    let output = timeout(
+        Duration::from_millis(500),
+        Command::new("getprop")
+            .arg(key)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| Error::Unknown(format!("Timed out reading property \"{key}\"")))??;
+
+    Ok(String::from_utf8(output.stdout)
+        .map_err(|err| Error::Unknown(format!("Property \"{key}\" was not valid UTF-8: {err}")))?
+        .trim()
+        .to_string())
+}
+
+/// Writes a single line to Android's logcat under `tag` at `priority` (one of logcat's `log`
+/// CLI priority letters, e.g. `'i'`/`'w'`), truncating it to `LOGCAT_LINE_LIMIT_BYTES` first
+pub async fn write_logcat_line(tag: &str, priority: char, line: &str) -> Result<()> {
+    let truncated = if line.len() > LOGCAT_LINE_LIMIT_BYTES {
+        let mut end = LOGCAT_LINE_LIMIT_BYTES;
+        while !line.is_char_boundary(end) {
+            end -= 1;
         }
+        &line[..end]
+    } else {
+        line
+    };
+
+    let status = Command::new("log")
+        .args(&["-t", tag, "-p", &priority.to_string(), truncated])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .status()
+        .await?;
 
-        return Ok(package_path.into());
+    if !status.success() {
+        return Err(Error::Unknown(format!(
+            "\"log\" exited with {status} writing a line for tag \"{tag}\""
+        )));
     }
 
-    Err(Error::PackageLookup(format!(
-        "Could not find package {package}"
-    )))
+    Ok(())
 }
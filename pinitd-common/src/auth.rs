@@ -0,0 +1,96 @@
+//! Authentication for the PMS and worker TCP sockets. Both accept connections from any
+//! local process with no other gatekeeping, so every connection starts with a challenge-response
+//! handshake proving the peer holds `AUTH_SECRET_FILE`'s shared secret, using the same
+//! argon2-over-a-nonce scheme as fabaccess-bffh. Only the connecting side proves itself; the
+//! accepting side is not authenticated to it. Run on every new TCP connection, including
+//! reconnects, so a resumed link re-proves identity rather than being trusted by UID alone.
+
+use rand::RngCore;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::{
+    AUTH_SECRET_FILE,
+    error::{Error, Result},
+    protocol::{
+        AuthChallenge, AuthResponse,
+        writable::{ProtocolRead, ProtocolWrite},
+    },
+};
+
+/// Length, in bytes, of the generated shared secret and of the challenge nonce
+const SECRET_LEN: usize = 32;
+
+/// Loads the shared secret from `AUTH_SECRET_FILE`, generating and persisting a new random one if
+/// it doesn't exist yet
+pub async fn load_or_create_secret() -> Result<Vec<u8>> {
+    if let Ok(existing) = tokio::fs::read(AUTH_SECRET_FILE).await {
+        if existing.len() == SECRET_LEN {
+            return Ok(existing);
+        }
+    }
+
+    let mut secret = vec![0u8; SECRET_LEN];
+    rand::thread_rng().fill_bytes(&mut secret);
+
+    if let Some(parent) = std::path::Path::new(AUTH_SECRET_FILE).parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(AUTH_SECRET_FILE, &secret).await?;
+
+    Ok(secret)
+}
+
+fn random_challenge() -> AuthChallenge {
+    let mut nonce = vec![0u8; SECRET_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    AuthChallenge { nonce }
+}
+
+fn prove(secret: &[u8], nonce: &[u8]) -> Result<AuthResponse> {
+    let proof = argon2::hash_raw(secret, nonce, &argon2::Config::default())
+        .map_err(|err| Error::AuthenticationError(format!("Failed to compute proof: {err}")))?;
+    Ok(AuthResponse { proof })
+}
+
+/// Compares two byte slices without early-exiting on the first mismatching byte, unlike `==`. Used
+/// to check a peer's submitted proof against the expected one so a forged proof's timing can't
+/// leak how many of its leading bytes happened to be correct.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Run by the accepting side of a connection (PMS, worker manager): challenges the peer and
+/// verifies their proof. Returns an error, which the caller should treat as grounds to drop the
+/// connection, on a bad proof or malformed response.
+pub async fn challenge_peer<S>(stream: &mut S, secret: &[u8]) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    let challenge = random_challenge();
+    challenge.write(stream).await?;
+
+    let response = AuthResponse::read(stream).await?;
+    let expected = prove(secret, &challenge.nonce)?;
+
+    if constant_time_eq(&expected.proof, &response.proof) {
+        Ok(())
+    } else {
+        Err(Error::AuthenticationError(
+            "Peer failed authentication challenge".into(),
+        ))
+    }
+}
+
+/// Run by the connecting side of a connection (wrapper, worker): answers the accepting side's
+/// challenge by proving knowledge of the shared secret
+pub async fn answer_challenge<S>(stream: &mut S, secret: &[u8]) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    let challenge = AuthChallenge::read(stream).await?;
+    let response = prove(secret, &challenge.nonce)?;
+    response.write(stream).await
+}
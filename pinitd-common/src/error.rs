@@ -12,6 +12,19 @@ pub enum Error {
     #[error("Bincode decode error {0}")]
     Decode(#[from] DecodeError),
 
+    #[error("Authentication error: {0}")]
+    AuthenticationError(String),
+
+    /// Uniform failure for a `shell::ShellCommand::run_captured` call: a non-zero exit, a
+    /// timeout, or a spawn/IO error all end up here rather than each call site mapping them
+    /// differently
+    #[error("Command `{command}` failed (exit code {exit_code:?}): {stderr}")]
+    CommandFailed {
+        command: String,
+        exit_code: Option<i32>,
+        stderr: String,
+    },
+
     #[error("Unknown error {0}")]
     Unknown(String),
 }
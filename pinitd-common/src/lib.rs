@@ -9,9 +9,12 @@ use std::{
 use crate::unit_config::ServiceConfig;
 
 pub mod android;
+pub mod auth;
 pub mod bincode;
 pub mod error;
+pub mod package_resolver;
 pub mod protocol;
+pub mod shell;
 pub mod unit_config;
 
 pub const CONTROL_SOCKET_ADDRESS: &str = "127.0.0.1:1717";
@@ -39,16 +42,49 @@ pub const ZYGOTE_READY_FILE: &str = "/sdcard/penumbra/etc/pinitd/zygote_ready";
 #[cfg(not(target_os = "android"))]
 pub const ZYGOTE_READY_FILE: &str = "test_data/pinitd/zygote_ready";
 
+#[cfg(target_os = "android")]
+pub const POLICY_FILE: &str = "/sdcard/penumbra/etc/pinitd/pinitd.policy";
+#[cfg(not(target_os = "android"))]
+pub const POLICY_FILE: &str = "test_data/pinitd/pinitd.policy";
+
+/// Directory a `ReadinessProbe::Notify` service's per-invocation notification socket is created
+/// in, named `<pinit_id>.sock` (see `wrapper::specialize_with_monitoring`)
+#[cfg(target_os = "android")]
+pub const NOTIFY_SOCKET_DIR: &str = "/sdcard/penumbra/etc/pinitd/notify/";
+#[cfg(not(target_os = "android"))]
+pub const NOTIFY_SOCKET_DIR: &str = "test_data/pinitd/notify/";
+
+/// Shared secret proven during the PMS/worker authentication handshake (see `auth` module).
+/// Generated on first run if it doesn't already exist.
+#[cfg(target_os = "android")]
+pub const AUTH_SECRET_FILE: &str = "/sdcard/penumbra/etc/pinitd/pinitd.secret";
+#[cfg(not(target_os = "android"))]
+pub const AUTH_SECRET_FILE: &str = "test_data/pinitd/pinitd.secret";
+
 pub const PACKAGE_NAME: &str = "com.penumbraos.pinitd";
 
 pub const WORKER_CONTROLLER_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
+/// How often a connected worker sends a `WorkerEvent::Heartbeat` to the controller
+pub const WORKER_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+/// Consecutive missed heartbeat intervals before the controller considers a worker connection
+/// dead, even if no read/write has outright failed yet
+pub const WORKER_HEARTBEAT_MISSED_THRESHOLD: u32 = 3;
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum ServiceRunState {
     Stopped,
     Stopping,
     Running { pid: Option<u32> },
     Failed { reason: String },
+    /// On-demand service idling until its activation trigger (socket connection/path) fires
+    Listening,
+    /// Attached but not yet considered up: waiting on a `ReadinessProbe::Notify` service to send
+    /// `READY=1` on its notification socket
+    Activating { pid: Option<u32> },
+    /// A `ReadinessProbe::Notify` watchdog deadline elapsed without a `WATCHDOG=1` datagram.
+    /// Transient: immediately handed to the restart policy like any other unexpected exit.
+    WatchdogFailed { reason: String },
 }
 
 impl std::fmt::Display for ServiceRunState {
@@ -62,10 +98,29 @@ impl std::fmt::Display for ServiceRunState {
                 pid.map_or("Unknown".into(), |pid| format!("{pid}"))
             ),
             Self::Failed { reason } => write!(f, "Failed: {}", reason),
+            Self::Listening => write!(f, "Listening (on-demand)"),
+            Self::Activating { pid } => write!(
+                f,
+                "Activating (PID: {})",
+                pid.map_or("Unknown".into(), |pid| format!("{pid}"))
+            ),
+            Self::WatchdogFailed { reason } => write!(f, "Watchdog failed: {}", reason),
         }
     }
 }
 
+/// A single `ServiceRunState` transition, carrying the service's current `enabled` flag,
+/// published on `ControllerRegistry`'s status event bus and streamed to `CLICommand::Watch`
+/// subscribers. `old_state` is the state the service held before the transition; loading a unit
+/// or changing its `enabled` flag publishes no delta.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ServiceStatusDelta {
+    pub name: String,
+    pub old_state: Option<ServiceRunState>,
+    pub new_state: ServiceRunState,
+    pub enabled: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ServiceStatus {
     pub name: String,
@@ -73,6 +128,10 @@ pub struct ServiceStatus {
     pub enabled: bool,
     pub state: ServiceRunState,
     pub config_path: PathBuf,
+    /// Number of automatic restarts recorded within the service's restart window
+    pub restart_count: u32,
+    /// Delay, in seconds, that the next automatic restart (if any) will be scheduled after
+    pub current_backoff_secs: u64,
 }
 
 pub fn create_core_directories() {
@@ -0,0 +1,96 @@
+//! Resolves `package:<name>/<relative path>` references embedded in `ServiceConfig` exec paths
+//! and arguments into concrete on-disk paths. A package's installed directory
+//! (`/data/app/<pkg>-<hash>/...`) changes on every reinstall, so configs can't hardcode it; this
+//! module looks the live base path up via `fetch_package_path` and caches it per package so a
+//! service with several references to the same package doesn't round-trip through `pm` for each
+//! one. Call `invalidate_cache` after a `Reload`/`ReloadAll` so a package reinstalled between
+//! reloads doesn't leave resolutions pointing at a stale directory.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use tokio::sync::Mutex;
+
+use crate::{
+    android::fetch_package_path,
+    error::{Error, Result},
+};
+
+/// Prefix marking a config value as a package-relative reference rather than a literal
+/// filesystem path, e.g. `package:com.example/lib/arm64/foo.so`
+pub const PACKAGE_REFERENCE_PREFIX: &str = "package:";
+
+fn cache() -> &'static Mutex<HashMap<String, String>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Drops every cached package base path. Wired to `Reload`/`ReloadAll` so the next resolution of
+/// a given package re-queries `pm` instead of reusing a path from before the reload.
+pub async fn invalidate_cache() {
+    cache().lock().await.clear();
+}
+
+/// Resolves `package`'s live base path (what `pm path` reports), using the per-package cache so
+/// repeat lookups of the same package don't shell out again until `invalidate_cache` runs.
+pub async fn cached_package_path(package: &str) -> Result<String> {
+    if let Some(path) = cache().lock().await.get(package) {
+        return Ok(path.clone());
+    }
+
+    let path = fetch_package_path(package).await?;
+    cache().lock().await.insert(package.to_string(), path.clone());
+    Ok(path)
+}
+
+/// Resolves a `package:<name>/<relative path>` reference into the concrete on-disk path inside
+/// that package's live install directory. `fetch_package_path` reports the installed APK file
+/// itself, so the reference is resolved relative to its containing directory. Rejects a
+/// reference with a `..` component, so a config can't use one to escape the package root.
+pub async fn resolve_package_reference(reference: &str) -> Result<PathBuf> {
+    let Some(rest) = reference.strip_prefix(PACKAGE_REFERENCE_PREFIX) else {
+        return Err(Error::Unknown(format!(
+            "\"{reference}\" is not a package reference (expected \"{PACKAGE_REFERENCE_PREFIX}<package>/<path>\")"
+        )));
+    };
+
+    let Some((package, relative_path)) = rest.split_once('/') else {
+        return Err(Error::Unknown(format!(
+            "Package reference \"{reference}\" is missing a path component"
+        )));
+    };
+
+    if relative_path.split('/').any(|component| component == "..") {
+        return Err(Error::Unknown(format!(
+            "Package reference \"{reference}\" may not traverse outside the package root"
+        )));
+    }
+
+    let base_path = cached_package_path(package).await?;
+    let base_dir = Path::new(&base_path).parent().unwrap_or(Path::new(&base_path));
+
+    Ok(base_dir.join(relative_path))
+}
+
+/// Expands every `package:<name>/<path>` reference appearing as a space-separated token in
+/// `input` into its resolved on-disk path, leaving every other token untouched. Only `' '`
+/// separates tokens; tabs and newlines do not. Lets a
+/// `ServiceCommandKind::Command`'s raw command string or arguments reference files inside an
+/// installed package without hardcoding its current `/data/app` directory.
+pub async fn expand_package_references(input: &str) -> Result<String> {
+    let mut expanded_words = Vec::new();
+
+    for word in input.split(' ') {
+        if word.starts_with(PACKAGE_REFERENCE_PREFIX) {
+            let resolved = resolve_package_reference(word).await?;
+            expanded_words.push(resolved.display().to_string());
+        } else {
+            expanded_words.push(word.to_string());
+        }
+    }
+
+    Ok(expanded_words.join(" "))
+}
@@ -3,10 +3,36 @@ use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use uuid::Uuid;
 use writable::{ProtocolRead, ProtocolWrite};
 
-use crate::{ServiceStatus, bincode::Bincodable, unit_config::ServiceConfig};
+use crate::{
+    ServiceStatus, ServiceStatusDelta, WorkerIdentity, bincode::Bincodable,
+    unit_config::ServiceConfig,
+};
 
 pub mod writable;
 
+/// Sent by the accepting side of a PMS/worker connection to challenge whoever just connected,
+/// before any other command on that connection is processed. See the `auth` module.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AuthChallenge {
+    pub nonce: Vec<u8>,
+}
+
+/// Sent by the connecting side in response to an `AuthChallenge`, proving knowledge of the shared
+/// secret without revealing it
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct AuthResponse {
+    pub proof: Vec<u8>,
+}
+
+impl Bincodable<'_> for AuthChallenge {}
+impl Bincodable<'_> for AuthResponse {}
+
+impl<T> ProtocolRead<'_, T> for AuthChallenge where T: AsyncReadExt + Unpin + Send {}
+impl<T> ProtocolRead<'_, T> for AuthResponse where T: AsyncReadExt + Unpin + Send {}
+
+impl<T> ProtocolWrite<'_, T> for AuthChallenge where T: AsyncWriteExt + Unpin + Send {}
+impl<T> ProtocolWrite<'_, T> for AuthResponse where T: AsyncWriteExt + Unpin + Send {}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum CLICommand {
     Start(String),
@@ -27,6 +53,26 @@ pub enum CLICommand {
     Shutdown,
     /// Hidden command used to signal Zygote has been restarted after reparenting cgroups. See #4 for more information
     ZygoteReady,
+    /// List every connected worker with its liveness state and hosted services
+    Workers,
+    /// Stop a worker from accepting new `SpawnProcess` commands, without affecting what it's
+    /// already hosting
+    PauseWorker(WorkerIdentity),
+    /// Undo a prior `PauseWorker`/`DrainWorker`
+    ResumeWorker(WorkerIdentity),
+    /// Like `PauseWorker`, but also shuts the worker down once it has no hosted services left
+    DrainWorker(WorkerIdentity),
+    /// Replay a service's buffered captured stdout/stderr. `lines` caps how many buffered entries
+    /// are replayed; if `follow` is set, the connection is kept open and new lines are streamed
+    /// as `CLIResponse::LogChunk` until the client disconnects
+    Logs {
+        name: String,
+        follow: bool,
+        lines: usize,
+    },
+    /// Stream `ServiceStatusDelta`s as `CLIResponse::StatusChange` until the client disconnects.
+    /// `name` restricts the stream to a single service; `None` watches every service.
+    Watch { name: Option<String> },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -37,6 +83,32 @@ pub enum CLIResponse {
     List(Vec<ServiceStatus>),
     Config(ServiceConfig),
     ShuttingDown,
+    Workers(Vec<WorkerSummary>),
+    /// One buffered or live-streamed log line for a `Logs` request. A `Success`/`Error` response
+    /// terminates the stream.
+    LogChunk(String),
+    /// One status transition streamed to a `Watch` subscriber
+    StatusChange(ServiceStatusDelta),
+}
+
+/// Liveness classification for a connected worker
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum WorkerLiveness {
+    /// Connected and hosting at least one running service
+    Active,
+    /// Connected but currently hosting no running services
+    Idle,
+    /// Failed to respond to the last liveness probe
+    Dead,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WorkerSummary {
+    pub identity: WorkerIdentity,
+    pub pid: usize,
+    pub liveness: WorkerLiveness,
+    pub last_seen_secs_ago: u64,
+    pub services: Vec<String>,
 }
 
 impl Bincodable<'_> for CLICommand {}
@@ -56,6 +128,24 @@ pub enum PMSFromRemoteCommand {
     ProcessAttached(u32),
     /// Process exit code
     ProcessExited(Option<i32>),
+    /// One line captured from the wrapped process's stdout/stderr. `is_stderr` lets the
+    /// controller preserve the originating stream when forwarding the line to a `LogSink`
+    /// (stdout -> info, stderr -> warn).
+    LogLine { line: String, is_stderr: bool },
+    /// A `READY=1`/`WATCHDOG=1` datagram received on a `ReadinessProbe::Notify` service's
+    /// notification socket
+    Notify(NotifyEvent),
+}
+
+/// A `KEY=VALUE` datagram a `ReadinessProbe::Notify` service can send on its notification
+/// socket, narrowed to the subset pinitd understands (systemd's `sd_notify` protocol has more;
+/// unrecognized keys are dropped by the wrapper before this is sent)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum NotifyEvent {
+    /// `READY=1`: the service has finished initializing
+    Ready,
+    /// `WATCHDOG=1`: the service is alive; pushes out its watchdog deadline
+    Watchdog,
 }
 
 // #[derive(Serialize, Deserialize, Debug)]
@@ -70,6 +160,12 @@ pub enum PMSToRemoteCommand {
     AllowStart,
     Kill,
     Ack,
+    /// Forward this signal to the monitored process via `kill(pid, sig)`
+    SendSignal(i32),
+    /// Gracefully stop the monitored process (SIGTERM)
+    Stop,
+    /// Stop monitoring the process, leaving it running
+    Detach,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -0,0 +1,138 @@
+use std::{collections::HashMap, path::PathBuf, process::Stdio, time::Duration};
+
+use tokio::{process::Command, time::timeout};
+
+use crate::error::{Error, Result};
+
+/// Default time budget for a `ShellCommand` that doesn't call `.timeout()` explicitly
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Decoded stdout/stderr and exit status from a completed `ShellCommand::run_captured` call
+#[derive(Debug, Clone)]
+pub struct CapturedOutput {
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Builder for running an external program with a bounded timeout and captured output,
+/// centralizing the spawn/pipe/timeout/error-mapping boilerplate that call sites like
+/// `fetch_package_path` previously hand-rolled against `tokio::process::Command` directly.
+pub struct ShellCommand {
+    program: String,
+    args: Vec<String>,
+    timeout: Duration,
+    working_dir: Option<PathBuf>,
+    env: HashMap<String, String>,
+    kill_on_drop: bool,
+}
+
+impl ShellCommand {
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+            working_dir: None,
+            env: HashMap::new(),
+            kill_on_drop: true,
+        }
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn working_dir(mut self, working_dir: impl Into<PathBuf>) -> Self {
+        self.working_dir = Some(working_dir.into());
+        self
+    }
+
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn kill_on_drop(mut self, kill_on_drop: bool) -> Self {
+        self.kill_on_drop = kill_on_drop;
+        self
+    }
+
+    fn command_line(&self) -> String {
+        if self.args.is_empty() {
+            self.program.clone()
+        } else {
+            format!("{} {}", self.program, self.args.join(" "))
+        }
+    }
+
+    /// Runs the command to completion, returning its decoded output. Fails uniformly - via
+    /// `Error::CommandFailed` - on a non-zero exit, a timeout, or a spawn/IO error, so callers no
+    /// longer need to distinguish those cases themselves.
+    pub async fn run_captured(&self) -> Result<CapturedOutput> {
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(self.kill_on_drop);
+
+        if let Some(working_dir) = &self.working_dir {
+            command.current_dir(working_dir);
+        }
+        for (key, value) in &self.env {
+            command.env(key, value);
+        }
+
+        let output = match timeout(self.timeout, command.output()).await {
+            Ok(Ok(output)) => output,
+            Ok(Err(err)) => {
+                return Err(Error::CommandFailed {
+                    command: self.command_line(),
+                    exit_code: None,
+                    stderr: err.to_string(),
+                });
+            }
+            Err(_) => {
+                return Err(Error::CommandFailed {
+                    command: self.command_line(),
+                    exit_code: None,
+                    stderr: format!("Timed out after {:?}", self.timeout),
+                });
+            }
+        };
+
+        let exit_code = output.status.code();
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+
+        if !output.status.success() {
+            return Err(Error::CommandFailed {
+                command: self.command_line(),
+                exit_code,
+                stderr,
+            });
+        }
+
+        Ok(CapturedOutput {
+            exit_code,
+            stdout,
+            stderr,
+        })
+    }
+}
@@ -3,7 +3,10 @@ use std::{fmt::Display, path::PathBuf};
 use dependency_graph::Node;
 use serde::{Deserialize, Serialize};
 
-use crate::{UID, android::fetch_package_path};
+use crate::{
+    UID,
+    package_resolver::{cached_package_path, expand_package_references, resolve_package_reference},
+};
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub enum RestartPolicy {
@@ -66,25 +69,23 @@ pub struct ServiceCommand {
 impl ServiceCommand {
     pub async fn command_string(&self) -> crate::error::Result<String> {
         let command = match &self.kind {
-            ServiceCommandKind::Command { command, .. } => command.clone(),
+            ServiceCommandKind::Command { command, .. } => expand_package_references(command).await?,
             ServiceCommandKind::LaunchPackageBinary {
                 package,
                 content_path,
                 args,
                 ..
             } => {
-                let package_path = fetch_package_path(&package).await?;
-                let path = PathBuf::from(package_path);
-                let path = path.join(
-                    content_path
-                        .strip_prefix("/")
-                        .unwrap_or_else(|| &content_path),
-                );
+                let trimmed_content_path = content_path.strip_prefix('/').unwrap_or(content_path);
+                let reference = format!("package:{package}/{trimmed_content_path}");
+                let path = resolve_package_reference(&reference).await?;
 
                 let command = path.display().to_string();
 
                 let command = if let Some(args) = args {
-                    format!("{command} {args}").trim().to_string()
+                    format!("{command} {}", expand_package_references(args).await?)
+                        .trim()
+                        .to_string()
                 } else {
                     command
                 };
@@ -102,7 +103,7 @@ impl ServiceCommand {
                 jvm_args,
                 ..
             } => {
-                let package_path = fetch_package_path(&package).await?;
+                let package_path = cached_package_path(package).await?;
 
                 let args = if let Some(command_args) = command_args {
                     command_args
@@ -155,28 +156,186 @@ impl Display for ServiceCommand {
     }
 }
 
+/// Current `ServiceConfig` schema version. Bump this alongside adding a migration step in
+/// `pinitd::unit_parsing` whenever a change to this struct would misparse an older unit file.
+pub const CURRENT_SERVICE_CONFIG_VERSION: u32 = 1;
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct ServiceConfig {
+    /// Schema version the unit file declared (or `1` if it declared none). Validated and
+    /// migrated forward to `CURRENT_SERVICE_CONFIG_VERSION` by `unit_parsing::migrate_config`
+    /// before the config is admitted to the registry.
+    pub config_version: u32,
     pub name: String,
     pub command: ServiceCommand,
     pub autostart: bool,
     pub restart: RestartPolicy,
+    /// Maximum number of automatic restarts allowed within `restart_window_secs` before the
+    /// service is considered crash-looping and transitioned to `Failed` instead of retried
+    pub restart_max: u32,
+    /// Rolling window, in seconds, over which `restart_max` restarts are counted
+    pub restart_window_secs: u64,
+    /// Delay, in milliseconds, before the first automatic restart after a crash
+    pub restart_delay_ms: u64,
+    /// Upper bound, in milliseconds, that the exponential restart backoff is capped at
+    pub restart_delay_max_ms: u64,
+    /// How long, in milliseconds, to wait after SIGTERM before escalating to SIGKILL when
+    /// stopping this service
+    pub stop_timeout_ms: u64,
     pub se_info: Option<String>,
     pub nice_name: Option<String>,
     pub unit_file_path: PathBuf,
     pub dependencies: ServiceDependencies,
+    pub activation: Activation,
+    pub readiness: ReadinessProbe,
+    /// Periodic liveness probe run while the service is `Running`, catching a process that's
+    /// alive but wedged, which a bare exit-code check would never notice
+    pub health_check: Option<HealthCheckConfig>,
+    /// Where this service's captured stdout/stderr is sent while it runs
+    pub logging: LogSink,
+    /// Gates this service on a late-boot predicate rather than starting it immediately:
+    /// `ControllerRegistry` holds it back out of the normal `autostart` pass and instead polls
+    /// this condition, calling `service_start` the first time it's satisfied. `None` leaves
+    /// `autostart` in charge as before.
+    pub condition: Option<ActivationCondition>,
+}
+
+/// Runs `command` every `interval_secs` while the service is `Running`. `failure_threshold`
+/// consecutive non-zero exits cause the registry to treat the service as failed, stopping it and
+/// handing the restart decision to the usual backoff/restart-policy machinery
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HealthCheckConfig {
+    pub command: String,
+    pub interval_secs: u64,
+    pub failure_threshold: u32,
+}
+
+/// Determines when a started service is considered genuinely up, rather than just attached.
+/// `None` treats the worker reporting a pid as immediately ready, matching the prior behavior;
+/// the other variants are polled after attachment and, if they don't pass within `timeout_secs`,
+/// the start is treated as a failure and handed to the service's restart policy/backoff.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub enum ReadinessProbe {
+    #[default]
+    None,
+    /// Ready once this path exists
+    FileExists { path: PathBuf, timeout_secs: u64 },
+    /// Ready once this command exits zero
+    CommandExitZero {
+        command: String,
+        timeout_secs: u64,
+    },
+    /// systemd `Type=notify` style: the service is handed a notification socket (via the
+    /// `NOTIFY_SOCKET` env var) and is ready once it sends a `READY=1` datagram on it, rather
+    /// than on worker attachment. The service is held in `ServiceRunState::Activating` until
+    /// then. If `watchdog_secs` is set, the service must keep sending `WATCHDOG=1` at least that
+    /// often once ready, or it's treated as crashed and handed to the restart policy.
+    Notify {
+        timeout_secs: u64,
+        watchdog_secs: Option<u64>,
+    },
+}
+
+/// Where a service's captured stdout/stderr is sent. The monitored wrapper forwards each line over
+/// the PMS connection as it is written, and `ControllerRegistry::append_log_line` keeps it in the
+/// service's log ring buffer and then hands it to this sink, rather than buffering to completion.
+/// `Null` sends it nowhere beyond that ring buffer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub enum LogSink {
+    #[default]
+    Null,
+    /// Write each line to Android's logcat, tagged with `nice_name`/`name`
+    Logcat,
+    /// Append each line to this file, rotating it once it grows past a size cap
+    File(PathBuf),
+}
+
+/// Controls when a service is started. `Immediate` services are started eagerly (subject to
+/// `autostart`/manual `Start`); `OnDemand` services are left stopped and only spawned once their
+/// trigger fires, so a service that is rarely used doesn't have to sit running from boot.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub enum Activation {
+    #[default]
+    Immediate,
+    OnDemand {
+        trigger: ActivationTrigger,
+        /// Stop the service and resume waiting for `trigger` after this many seconds of no
+        /// further activity. `None` means the service stays running once started.
+        idle_timeout_secs: Option<u64>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ActivationTrigger {
+    /// Listen on this address - either a TCP address (e.g. `127.0.0.1:9000`) or a unix socket
+    /// path - and start the service on the first inbound connection. The controller only uses
+    /// its own bind of this address to detect demand; the worker rebinds it again and hands the
+    /// listening socket off to the spawned process as an inherited file descriptor (see
+    /// `registry::spawn::bind_activation_listener`), so the child should accept on the inherited
+    /// listener (`LISTEN_FDS=1`, fd 3) rather than binding its own.
+    Socket(String),
+    /// Poll for this path to appear; the first time it does, the service is started
+    Path(PathBuf),
+}
+
+/// A predicate an `autostart`-gated service can be held behind until it first becomes true, so
+/// late-boot services can be sequenced without hard-coding them into the boot path
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ActivationCondition {
+    /// Satisfied once this path exists
+    PathExists(PathBuf),
+    /// Satisfied once the Android system property `key` equals `value`
+    Property { key: String, value: String },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct ServiceDependencies {
+    /// Soft dependency: started before this service when present, but a failure is only logged
     pub wants: Vec<String>,
+    /// Hard dependency: must reach `Running` before this service starts, or the start aborts
+    pub requires: Vec<String>,
+    /// Ordering-only: this service starts after the named services, without requiring them
+    pub after: Vec<String>,
+    /// Ordering-only: this service starts before the named services
+    pub before: Vec<String>,
+    /// This service cannot run at the same time as the named services
+    pub conflicts: Vec<String>,
+    /// Combined forward-ordering edges (`wants` + `requires` + `after`, plus any `before` edge
+    /// pointing at this service from elsewhere) driving the topological sort.
+    /// `ServiceDependencies::new` fills in the `wants`/`requires`/`after` part; `ControllerRegistry`
+    /// adds the `before` edges declared by other units when building the dependency graph.
+    #[serde(default)]
+    pub ordering_edges: Vec<String>,
+}
+
+impl ServiceDependencies {
+    pub fn new(
+        wants: Vec<String>,
+        requires: Vec<String>,
+        after: Vec<String>,
+        before: Vec<String>,
+        conflicts: Vec<String>,
+    ) -> Self {
+        let mut ordering_edges = wants.clone();
+        ordering_edges.extend(requires.iter().cloned());
+        ordering_edges.extend(after.iter().cloned());
+
+        Self {
+            wants,
+            requires,
+            after,
+            before,
+            conflicts,
+            ordering_edges,
+        }
+    }
 }
 
 impl Node for ServiceConfig {
     type DependencyType = String;
 
     fn dependencies(&self) -> &[Self::DependencyType] {
-        &self.dependencies.wants
+        &self.dependencies.ordering_edges
     }
 
     fn matches(&self, dependency: &Self::DependencyType) -> bool {
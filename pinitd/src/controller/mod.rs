@@ -1,7 +1,7 @@
 use std::{process, sync::Arc, time::Duration};
 
 use pinitd_common::{
-    CONTROL_SOCKET_ADDRESS, create_core_directories,
+    CONTROL_SOCKET_ADDRESS, UID, create_core_directories,
     protocol::{
         CLICommand, CLIResponse,
         writable::{ProtocolRead, ProtocolWrite},
@@ -9,7 +9,7 @@ use pinitd_common::{
 };
 use pms::ProcessManagementService;
 use tokio::{
-    io::AsyncRead,
+    io::{AsyncRead, AsyncWrite},
     net::TcpListener,
     signal::unix::{SignalKind, signal},
     sync::{Mutex, mpsc},
@@ -23,6 +23,7 @@ use crate::{
     error::Result,
     exploit::{exploit, init_exploit, trigger_exploit_crash},
     file::acquire_controller_lock,
+    policy::resolve_peer_uid,
     registry::{Registry, controller::ControllerRegistry},
     worker::protocol::WorkerEvent,
     wrapper::daemonize,
@@ -69,9 +70,15 @@ impl Controller {
 
             let (worker_event_tx, global_worker_event_rx) = mpsc::channel::<WorkerEvent>(100);
 
+            let local_uid = if use_system_domain {
+                pinitd_common::UID::System
+            } else {
+                pinitd_common::UID::Shell
+            };
             let mut registry = ControllerRegistry::new(
                 worker_event_tx,
                 Arc::new(Mutex::new(Some(controller_lock))),
+                local_uid,
             )
             .await?;
             let pms = ProcessManagementService::new(registry.clone()).await?;
@@ -79,6 +86,10 @@ impl Controller {
             let mut controller = Controller { registry };
 
             controller.registry.load_from_disk().await?;
+            controller.registry.start_config_watcher();
+            controller.registry.start_on_demand_activations().await;
+            controller.registry.start_condition_gated_activations().await;
+            controller.registry.start_worker_liveness_monitor();
             let post_exploit = controller.registry.setup_workers().await?;
 
             let shutdown_token = CancellationToken::new();
@@ -142,19 +153,36 @@ impl Controller {
 
         loop {
             match control_socket.accept().await {
-                Ok((mut stream, _)) => {
-                    info!("Accepted new client connection");
+                Ok((mut stream, peer_addr)) => {
+                    info!("Accepted new client connection from {peer_addr}");
+                    let local_addr = stream.local_addr()?;
                     let mut controller_clone = self.clone();
                     let shutdown_token_clone = shutdown_token.clone();
                     tokio::spawn(async move {
+                        let actor = match resolve_peer_uid(peer_addr, local_addr).await {
+                            Ok(actor) => actor,
+                            Err(err) => {
+                                error!("Refusing unidentifiable CLI connection: {err}");
+                                let _ = CLIResponse::Error(
+                                    "Could not verify caller identity".into(),
+                                )
+                                .write(&mut stream)
+                                .await;
+                                return;
+                            }
+                        };
+
                         match controller_clone
-                            .handle_command(&mut stream, shutdown_token_clone)
+                            .handle_command(&mut stream, actor, shutdown_token_clone)
                             .await
                         {
-                            Ok(response) => match response.write(&mut stream).await {
+                            Ok(Some(response)) => match response.write(&mut stream).await {
                                 Ok(_) => {}
                                 Err(err) => error!("Error responding to client: {err:?}"),
                             },
+                            // Logs { follow: true } / Watch write their own stream of responses
+                            // directly
+                            Ok(None) => {}
                             Err(err) => error!("Error handling client: {err:?}"),
                         }
                     });
@@ -166,23 +194,44 @@ impl Controller {
         }
     }
 
+    /// Reads and dispatches a single `CLICommand`. Returns `Ok(None)` for `Logs`/`Watch`
+    /// requests, which write their own (possibly multi-message) response directly to `stream`
+    /// instead of handing back a single `CLIResponse`.
     async fn handle_command<T>(
         &mut self,
         stream: &mut T,
+        actor: UID,
         shutdown_token: CancellationToken,
-    ) -> Result<CLIResponse>
+    ) -> Result<Option<CLIResponse>>
     where
-        T: AsyncRead + Unpin + Send,
+        T: AsyncRead + AsyncWrite + Unpin + Send,
     {
         let command = CLICommand::read(stream).await?;
-        info!("Received CLICommand: {:?}", command);
+        info!("Received CLICommand from {actor:?}: {:?}", command);
+
+        if let CLICommand::Logs {
+            name,
+            follow,
+            lines,
+        } = command
+        {
+            self.registry
+                .stream_logs(stream, &actor, name, follow, lines)
+                .await?;
+            return Ok(None);
+        }
+
+        if let CLICommand::Watch { name } = command {
+            self.registry.stream_status(stream, &actor, name).await?;
+            return Ok(None);
+        }
 
         let response = self
             .registry
-            .process_remote_command(command, shutdown_token)
+            .process_remote_command(command, actor, shutdown_token)
             .await;
 
-        Ok(response)
+        Ok(Some(response))
     }
 }
 
@@ -209,7 +258,7 @@ fn setup_signal_watchers(shutdown_token: CancellationToken) -> Result<JoinHandle
 
 async fn shutdown(registry: ControllerRegistry) -> Result<()> {
     info!("Initiating daemon shutdown...");
-    registry.shutdown().await?;
+    registry.shutdown(true).await?;
 
     info!("Goodbye");
 
@@ -5,7 +5,8 @@ use crate::{
     registry::controller::ControllerRegistry,
 };
 use pinitd_common::{
-    PMS_SOCKET_ADDRESS, ServiceRunState,
+    PMS_SOCKET_ADDRESS,
+    auth,
     bincode::Bincodable,
     protocol::{
         PMSFromRemoteCommand, PMSToRemoteCommand,
@@ -14,8 +15,9 @@ use pinitd_common::{
 };
 use tokio::{
     io::{self, AsyncRead, AsyncWriteExt},
-    net::{TcpListener, tcp::OwnedWriteHalf},
+    net::{TcpListener, TcpStream, tcp::OwnedWriteHalf},
     sync::Mutex,
+    time::{Duration, timeout},
 };
 use uuid::Uuid;
 
@@ -45,10 +47,15 @@ impl ProcessManagementService {
         tokio::spawn(async move {
             loop {
                 match socket.accept().await {
-                    Ok((stream, _)) => {
+                    Ok((mut stream, _)) => {
                         info!("Accepted PMS connection");
                         let mut pms_clone = inner_pms.clone();
                         tokio::spawn(async move {
+                            if let Err(err) = Self::authenticate(&mut stream).await {
+                                warn!("PMS connection failed authentication: {err}");
+                                return;
+                            }
+
                             let (mut stream_rx, stream_tx) = stream.into_split();
                             let stream_tx = Arc::new(Mutex::new(stream_tx));
                             // let mut is_first_command = true;
@@ -110,6 +117,14 @@ impl ProcessManagementService {
         Ok(pms)
     }
 
+    /// Challenges a freshly-accepted connection before any command on it is processed. Run for
+    /// every new connection, including reconnects after a dropped link, so a resumed wrapper
+    /// proves identity again rather than being trusted by it having connected before.
+    async fn authenticate(stream: &mut TcpStream) -> Result<()> {
+        let secret = auth::load_or_create_secret().await?;
+        Ok(timeout(Duration::from_secs(5), auth::challenge_peer(stream, &secret)).await??)
+    }
+
     pub async fn register_spawn(&self, id: Uuid, service_name: String) {
         self.zygote_ids.lock().await.insert(id, service_name);
     }
@@ -121,6 +136,24 @@ impl ProcessManagementService {
         }
     }
 
+    /// Push a control command directly to a running wrapper's PMS connection, out of band from
+    /// the request/response loop in `handle_command`. Lets the controller signal, gracefully
+    /// stop, or detach a process spawned through the Zygote path while it's still running,
+    /// rather than only ever observing what it reports.
+    pub async fn send_command_to_wrapper(
+        &self,
+        service_name: &str,
+        command: PMSToRemoteCommand,
+    ) -> Result<()> {
+        let registrations = self.zygote_registrations.lock().await;
+        let connection = registrations
+            .get(service_name)
+            .ok_or_else(|| Error::UnknownServiceError(service_name.to_string()))?;
+
+        let mut write_lock = connection.stream_tx.lock().await;
+        Ok(command.write(&mut *write_lock).await?)
+    }
+
     async fn handle_command<T>(
         &mut self,
         stream_rx: &mut T,
@@ -181,18 +214,43 @@ impl ProcessManagementService {
             }
             PMSFromRemoteCommand::ProcessAttached(pid) => {
                 let connection = connection.as_mut().unwrap();
+                info!("Received pid {pid} for \"{}\"", connection.service_name);
                 self.registry
-                    .update_service_state(
-                        connection.service_name.clone(),
-                        ServiceRunState::Running { pid: Some(pid) },
-                    )
+                    .handle_process_attached(connection.service_name.clone(), pid)
                     .await?;
-                info!("Received pid {pid} for \"{}\"", connection.service_name);
 
                 Ok(Some(PMSToRemoteCommand::Ack))
             }
-            PMSFromRemoteCommand::ProcessExited(_exit_code) => {
-                // TODO: Implement
+            PMSFromRemoteCommand::ProcessExited(exit_code) => {
+                let service_name = connection.as_ref().unwrap().service_name.clone();
+
+                // Drop the zygote registration before handing off to the restart machinery, so a
+                // restart's new `WrapperLaunched` isn't mistaken for issue #4's duplicate spawn
+                self.clear_service(&service_name).await;
+
+                let exit_reason = exit_code
+                    .map(|code| code.to_string())
+                    .unwrap_or_else(|| "unknown".to_string());
+                info!("Process exited for \"{service_name}\" (code: {exit_reason})");
+
+                self.registry
+                    .handle_unexpected_exit(service_name, exit_reason)
+                    .await?;
+
+                Ok(None)
+            }
+            PMSFromRemoteCommand::LogLine { line, is_stderr } => {
+                let connection = connection.as_ref().unwrap();
+                self.registry
+                    .append_log_line(&connection.service_name, line, is_stderr)
+                    .await;
+                Ok(None)
+            }
+            PMSFromRemoteCommand::Notify(event) => {
+                let connection = connection.as_ref().unwrap();
+                self.registry
+                    .handle_notify(&connection.service_name, event)
+                    .await;
                 Ok(None)
             }
         }
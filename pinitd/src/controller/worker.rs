@@ -43,10 +43,11 @@ async fn handle_worker_event(registry: &ControllerRegistry, event: WorkerEvent)
             }
         }
         WorkerEvent::Heartbeat { .. } => {
-            // TODO: Do something?
-            // info!(
-            //     "Worker {worker_uid:?} heartbeat: uptime={uptime_seconds}s, active={active_services}",
-            // );
+            // Liveness tracking happens in WorkerConnection's read loop as soon as this event is
+            // received, regardless of what (if anything) we do with it here
+        }
+        WorkerEvent::Drained => {
+            info!("Worker drained; all of its processes have exited and it is shutting down");
         }
         WorkerEvent::ProcessSpawned {
             service_name,
@@ -66,7 +67,8 @@ async fn handle_worker_event(registry: &ControllerRegistry, event: WorkerEvent)
             info!("Process exited: {} (code: {})", service_name, exit_code);
 
             registry
-                .update_service_state(service_name, ServiceRunState::Stopped)
+                .clone()
+                .handle_unexpected_exit(service_name, exit_code.to_string())
                 .await?;
         }
         WorkerEvent::ProcessCrashed {
@@ -76,12 +78,8 @@ async fn handle_worker_event(registry: &ControllerRegistry, event: WorkerEvent)
             error!("Process crashed: {service_name} (signal: {signal})");
 
             registry
-                .update_service_state(
-                    service_name,
-                    ServiceRunState::Failed {
-                        reason: format!("Process crashed with signal {}", signal),
-                    },
-                )
+                .clone()
+                .handle_unexpected_exit(service_name, format!("signal {signal}"))
                 .await?;
         }
         WorkerEvent::WorkerError {
@@ -1,6 +1,8 @@
 use std::{collections::HashMap, sync::Arc, time::Duration};
 
-use pinitd_common::{UID, WORKER_CONTROLLER_POLL_INTERVAL, WORKER_SOCKET_ADDRESS, WorkerIdentity};
+use pinitd_common::{
+    UID, WORKER_CONTROLLER_POLL_INTERVAL, WORKER_SOCKET_ADDRESS, WorkerIdentity, auth,
+};
 use tokio::{
     net::TcpListener,
     sync::{Mutex, mpsc, oneshot},
@@ -82,13 +84,18 @@ impl WorkerManager {
     }
 
     async fn handle_new_connection(
-        stream: tokio::net::TcpStream,
+        mut stream: tokio::net::TcpStream,
         workers: Arc<Mutex<HashMap<WorkerIdentity, WorkerConnection>>>,
         pending_connections: Arc<
             Mutex<HashMap<WorkerIdentity, Vec<oneshot::Sender<WorkerConnection>>>>,
         >,
         event_tx: mpsc::Sender<WorkerEvent>,
     ) -> Result<()> {
+        // Challenge the connecting worker before trusting anything it sends, including on a
+        // reconnect after a dropped link
+        let secret = auth::load_or_create_secret().await?;
+        timeout(Duration::from_secs(5), auth::challenge_peer(&mut stream, &secret)).await??;
+
         // Create connection from stream
         let connection = Connection::from(stream, true);
 
@@ -183,6 +190,37 @@ impl WorkerManager {
             .collect()
     }
 
+    /// Probes every connected worker. A worker that fails to respond (including a timeout) is
+    /// marked disconnected by `WorkerConnection::ping` and reaped by the existing
+    /// `monitor_until_disconnect` task, just like any other lost connection.
+    pub async fn ping_all(&self) {
+        for worker in self.all_workers().await {
+            if let Err(err) = worker.ping().await {
+                warn!("Worker {:?} failed liveness probe: {err}", worker.uid());
+            }
+        }
+    }
+
+    /// Stops `identity`'s worker from accepting new spawns, without affecting what it's already
+    /// hosting
+    pub async fn pause_worker(&self, identity: &WorkerIdentity) -> Result<()> {
+        self.get_worker_for_identity(identity).await?.pause().await
+    }
+
+    /// Undoes a prior `pause_worker`/`drain_worker` for `identity`
+    pub async fn resume_worker(&self, identity: &WorkerIdentity) -> Result<()> {
+        self.get_worker_for_identity(identity)
+            .await?
+            .resume()
+            .await
+    }
+
+    /// Like `pause_worker`, but `identity`'s worker also shuts itself down once it finishes
+    /// whatever it's currently hosting
+    pub async fn drain_worker(&self, identity: &WorkerIdentity) -> Result<()> {
+        self.get_worker_for_identity(identity).await?.drain().await
+    }
+
     async fn spawn_worker(
         &self,
         identity: &WorkerIdentity,
@@ -38,6 +38,10 @@ pub enum Error {
     CommonError(#[from] pinitd_common::error::Error),
     #[error("Zygote error: {0}")]
     ZygoteError(String),
+    #[error("Config watcher error: {0}")]
+    NotifyError(#[from] notify::Error),
+    #[error("Authentication error: {0}")]
+    AuthenticationError(String),
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
@@ -21,6 +21,7 @@ mod controller;
 mod error;
 #[cfg(not(target_os = "android"))]
 mod log;
+mod policy;
 mod registry;
 mod state;
 mod types;
@@ -157,7 +158,7 @@ async fn run() -> Result<()> {
         }
         Args::InternalSpawnWrapper(args) => {
             init_logging_with_tag("pinitd-wrapper-int".into());
-            Wrapper::specialize_without_monitoring(args.command, args.is_zygote, true).await?;
+            Wrapper::specialize_without_monitoring(args.command, args.is_zygote, None).await?;
             Ok(())
         }
     }
@@ -0,0 +1,227 @@
+use std::{net::SocketAddr, path::Path};
+
+use ini::Ini;
+use pinitd_common::UID;
+use tokio::fs;
+
+use crate::error::{Error, Result};
+
+/// A single operation gated by the policy file. Kept separate from `CLICommand` so that
+/// commands sharing an underlying capability (e.g. `Reload`/`ReloadAll`) can be granted
+/// independently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Start,
+    Stop,
+    Restart,
+    Enable,
+    Disable,
+    Reload,
+    ReloadAll,
+    Config,
+    Status,
+    List,
+    /// Gated separately from every other action: granting a wildcard over services does not
+    /// imply the ability to bring the whole daemon down.
+    Shutdown,
+    ZygoteReady,
+    /// List connected workers and their liveness state
+    Workers,
+    /// Pause, resume, or drain a worker. Gated separately from `Workers`: being able to see the
+    /// worker pool doesn't imply being able to steer it.
+    ControlWorkers,
+    /// Read a service's captured stdout/stderr
+    Logs,
+}
+
+impl Action {
+    const ALL: [Action; 15] = [
+        Action::Start,
+        Action::Stop,
+        Action::Restart,
+        Action::Enable,
+        Action::Disable,
+        Action::Reload,
+        Action::ReloadAll,
+        Action::Config,
+        Action::Status,
+        Action::List,
+        Action::Shutdown,
+        Action::ZygoteReady,
+        Action::Workers,
+        Action::ControlWorkers,
+        Action::Logs,
+    ];
+
+    fn parse_list(value: &str) -> Result<Vec<Action>> {
+        if value.trim() == "*" {
+            return Ok(Self::ALL.to_vec());
+        }
+
+        value
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| match s.to_ascii_lowercase().as_str() {
+                "start" => Ok(Action::Start),
+                "stop" => Ok(Action::Stop),
+                "restart" => Ok(Action::Restart),
+                "enable" => Ok(Action::Enable),
+                "disable" => Ok(Action::Disable),
+                "reload" => Ok(Action::Reload),
+                "reloadall" => Ok(Action::ReloadAll),
+                "config" => Ok(Action::Config),
+                "status" => Ok(Action::Status),
+                "list" => Ok(Action::List),
+                "shutdown" => Ok(Action::Shutdown),
+                "zygoteready" => Ok(Action::ZygoteReady),
+                "workers" => Ok(Action::Workers),
+                "controlworkers" => Ok(Action::ControlWorkers),
+                "logs" => Ok(Action::Logs),
+                other => Err(Error::ConfigError(format!(
+                    "Unknown policy action \"{other}\""
+                ))),
+            })
+            .collect()
+    }
+}
+
+/// Grants `actor` the ability to perform `actions` against services whose name matches one of
+/// `service_patterns` (`*` matches every service, and is implied for actions without a service
+/// target, e.g. `List`/`Shutdown`).
+#[derive(Debug, Clone)]
+struct PolicyRule {
+    actor: UID,
+    service_patterns: Vec<String>,
+    actions: Vec<Action>,
+}
+
+impl PolicyRule {
+    fn allows(&self, service: Option<&str>, action: Action) -> bool {
+        if !self.actions.contains(&action) {
+            return false;
+        }
+
+        match service {
+            None => true,
+            Some(name) => self
+                .service_patterns
+                .iter()
+                .any(|pattern| pattern == "*" || pattern == name),
+        }
+    }
+}
+
+/// Authorization policy mapping a caller identity (the UID of the process connected to the
+/// control socket) to the services and actions it may invoke. Loaded once at startup from
+/// `POLICY_FILE`; an absent or empty file falls back to a `System`-only default so a fresh
+/// install is not left wide open.
+#[derive(Debug, Clone, Default)]
+pub struct AuthPolicy {
+    rules: Vec<PolicyRule>,
+}
+
+impl AuthPolicy {
+    pub async fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            warn!("No policy file found at {path:?}, defaulting to System-only access");
+            return Ok(Self::default_system_only());
+        }
+
+        let content = fs::read_to_string(path).await?;
+        let ini = Ini::load_from_str(&content)
+            .map_err(|e| Error::ConfigError(format!("Policy parsing error: {e}")))?;
+
+        let mut rules = Vec::new();
+        for (section, properties) in ini.iter() {
+            let Some(section) = section else {
+                continue;
+            };
+
+            let actor: UID = section
+                .trim()
+                .try_into()
+                .map_err(|err| Error::ConfigError(format!("Invalid actor \"{section}\": {err}")))?;
+            let service_patterns = properties
+                .get("Services")
+                .unwrap_or("*")
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .collect();
+            let actions = Action::parse_list(properties.get("Actions").unwrap_or(""))?;
+
+            rules.push(PolicyRule {
+                actor,
+                service_patterns,
+                actions,
+            });
+        }
+
+        if rules.is_empty() {
+            warn!("Policy file {path:?} contained no rules, defaulting to System-only access");
+            return Ok(Self::default_system_only());
+        }
+
+        Ok(Self { rules })
+    }
+
+    fn default_system_only() -> Self {
+        Self {
+            rules: vec![PolicyRule {
+                actor: UID::System,
+                service_patterns: vec!["*".into()],
+                actions: Action::ALL.to_vec(),
+            }],
+        }
+    }
+
+    pub fn is_allowed(&self, actor: &UID, service: Option<&str>, action: Action) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| &rule.actor == actor && rule.allows(service, action))
+    }
+}
+
+/// Resolves the UID that owns the peer end of a loopback TCP connection by cross-referencing
+/// `/proc/net/tcp`. The control socket is plain TCP (not a unix domain socket), so there is no
+/// `SO_PEERCRED`; on Linux every socket's owning UID is still visible in `/proc/net/tcp`, and
+/// since both ends of the connection live on the same loopback interface we can match the
+/// accepted stream's peer address/port against that table's `local_address` column.
+pub async fn resolve_peer_uid(peer_addr: SocketAddr, local_addr: SocketAddr) -> Result<UID> {
+    let contents = fs::read_to_string("/proc/net/tcp").await?;
+    let target_local = encode_hex_addr(peer_addr)?;
+    let target_remote = encode_hex_addr(local_addr)?;
+
+    for line in contents.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 8 {
+            continue;
+        }
+
+        if fields[1].eq_ignore_ascii_case(&target_local) && fields[2].eq_ignore_ascii_case(&target_remote)
+        {
+            return fields[7]
+                .try_into()
+                .map_err(|err| Error::Unknown(format!("Could not parse peer UID: {err}")));
+        }
+    }
+
+    Err(Error::Unknown(format!(
+        "Could not resolve owning UID for peer {peer_addr}"
+    )))
+}
+
+fn encode_hex_addr(addr: SocketAddr) -> Result<String> {
+    match addr {
+        SocketAddr::V4(addr) => {
+            let octets = addr.ip().octets();
+            Ok(format!(
+                "{:02X}{:02X}{:02X}{:02X}:{:04X}",
+                octets[3], octets[2], octets[1], octets[0], addr.port()
+            ))
+        }
+        SocketAddr::V6(_) => Err(Error::Unknown(
+            "IPv6 control connections are not supported".into(),
+        )),
+    }
+}
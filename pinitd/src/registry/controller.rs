@@ -10,13 +10,23 @@ use std::{
 use dependency_graph::{DependencyGraph, Step};
 use file_lock::FileLock;
 use pinitd_common::{
-    CONFIG_DIR, ENABLED_DIR, ServiceRunState, ServiceStatus, UID, WorkerIdentity,
-    ZYGOTE_READY_FILE,
-    protocol::{CLICommand, CLIResponse},
-    unit_config::ServiceConfig,
+    CONFIG_DIR, ENABLED_DIR, POLICY_FILE, ServiceRunState, ServiceStatus, ServiceStatusDelta, UID,
+    WORKER_CONTROLLER_POLL_INTERVAL, WorkerIdentity, ZYGOTE_READY_FILE,
+    android::{read_property, write_logcat_line},
+    package_resolver,
+    protocol::{
+        CLICommand, CLIResponse, NotifyEvent, WorkerLiveness, WorkerSummary,
+        writable::ProtocolWrite,
+    },
+    unit_config::{
+        Activation, ActivationCondition, ActivationTrigger, LogSink, ReadinessProbe,
+        RestartPolicy, ServiceConfig,
+    },
 };
 use tokio::{
     fs,
+    io::AsyncWriteExt,
+    net::{TcpListener, UnixListener},
     sync::{Mutex, mpsc},
     time::{sleep, timeout},
 };
@@ -27,13 +37,98 @@ use crate::{
     controller::{pms::ProcessManagementService, worker_manager::WorkerManager},
     error::{Error, Result},
     file::acquire_controller_lock,
+    policy::{Action, AuthPolicy},
     types::Service,
-    unit_parsing::ParsableServiceConfig,
+    unit_parsing::{
+        ParsableServiceConfig, apply_override, base_unit_path_for, migrate_config,
+        rewrite_config_version,
+    },
     worker::protocol::{WorkerCommand, WorkerEvent, WorkerResponse},
 };
 
 use super::Registry;
 
+/// How often the controller pings every connected worker to detect dead ones
+const WORKER_LIVENESS_POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// How often a readiness probe is re-checked while waiting for it to pass
+const READINESS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Ceiling on the bytes of captured log lines retained per service, oldest lines dropped first
+const LOG_RING_CAPACITY_BYTES: usize = 64 * 1024;
+/// Backlog of live lines a slow `Logs --follow` subscriber can fall behind by before older ones
+/// are silently dropped for it (it keeps following from whatever's next, not resynced from zero)
+const LOG_FOLLOW_CHANNEL_CAPACITY: usize = 256;
+/// Backlog of `ServiceStatusDelta`s a slow `Watch` subscriber can fall behind by before older ones
+/// are dropped for it, so a stalled watcher can't back up the daemon
+const STATUS_CHANNEL_CAPACITY: usize = 256;
+
+/// Bounded ring buffer of a single service's captured stdout/stderr, plus a broadcast channel so
+/// `Logs { follow: true }` requests can be handed new lines as they arrive.
+struct ServiceLogBuffer {
+    lines: std::collections::VecDeque<String>,
+    total_bytes: usize,
+    live_tx: tokio::sync::broadcast::Sender<String>,
+}
+
+impl ServiceLogBuffer {
+    fn new() -> Self {
+        let (live_tx, _) = tokio::sync::broadcast::channel(LOG_FOLLOW_CHANNEL_CAPACITY);
+        Self {
+            lines: std::collections::VecDeque::new(),
+            total_bytes: 0,
+            live_tx,
+        }
+    }
+
+    fn push(&mut self, line: String) {
+        self.total_bytes += line.len();
+        self.lines.push_back(line.clone());
+        while self.total_bytes > LOG_RING_CAPACITY_BYTES {
+            match self.lines.pop_front() {
+                Some(removed) => self.total_bytes -= removed.len(),
+                None => break,
+            }
+        }
+        // No subscribers is the common case (nobody's following); ignore the error
+        let _ = self.live_tx.send(line);
+    }
+
+    fn tail(&self, lines: usize) -> Vec<String> {
+        let skip = self.lines.len().saturating_sub(lines);
+        self.lines.iter().skip(skip).cloned().collect()
+    }
+}
+
+/// Checks whether an `ActivationCondition` currently holds
+async fn condition_satisfied(condition: &ActivationCondition) -> bool {
+    match condition {
+        ActivationCondition::PathExists(path) => fs::metadata(path).await.is_ok(),
+        ActivationCondition::Property { key, value } => {
+            matches!(read_property(key).await, Ok(current) if &current == value)
+        }
+    }
+}
+
+/// A `ReadinessProbe`, resolved into something that can actually be checked
+enum ReadinessCheck {
+    FileExists(PathBuf),
+    CommandExitZero(String),
+}
+
+impl ReadinessCheck {
+    async fn passes(&self) -> bool {
+        match self {
+            ReadinessCheck::FileExists(path) => fs::metadata(path).await.is_ok(),
+            ReadinessCheck::CommandExitZero(command) => tokio::process::Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .status()
+                .await
+                .map(|status| status.success())
+                .unwrap_or(false),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct ControllerRegistry {
     controller_lock: Arc<Mutex<Option<FileLock>>>,
@@ -42,12 +137,33 @@ pub struct ControllerRegistry {
     worker_manager: Arc<WorkerManager>,
     service_spawning_allowed: Arc<Mutex<bool>>,
     pending_autostart_services: Arc<Mutex<Option<Vec<String>>>>,
+    /// UID used for commands (e.g. `PackageActivity`) that must always run as the controller
+    local_uid: UID,
+    /// Maps connecting CLI identities to the services/actions they're allowed to invoke
+    policy: Arc<AuthPolicy>,
+    /// One entry per on-demand service currently waiting on its activation trigger; cancelling
+    /// the token stops that wait loop (used when the unit is reloaded or removed)
+    activation_tasks: Arc<Mutex<HashMap<String, CancellationToken>>>,
+    /// One entry per `condition`-gated service currently waiting for its predicate to become
+    /// true; cancelling the token stops that wait loop (used when the unit is reloaded or removed)
+    condition_tasks: Arc<Mutex<HashMap<String, CancellationToken>>>,
+    /// Captured stdout/stderr ring buffer per service, fed by `PMSFromRemoteCommand::LogLine`
+    service_logs: Arc<Mutex<HashMap<String, ServiceLogBuffer>>>,
+    /// Fan-out bus of `ServiceStatusDelta`s, published by `update_service_state`. Subscribed to by
+    /// `Watch` requests; a lagging subscriber just misses older deltas rather than stalling
+    /// publishers.
+    status_tx: tokio::sync::broadcast::Sender<ServiceStatusDelta>,
+    /// One entry per service currently in `run_notify_watch`, fed by `handle_notify` as
+    /// `PMSFromRemoteCommand::Notify` messages arrive. Removed once the watch finishes (ready
+    /// times out, or the service stops being `Running`/`Activating`).
+    notify_channels: Arc<Mutex<HashMap<String, mpsc::UnboundedSender<NotifyEvent>>>>,
 }
 
 impl ControllerRegistry {
     pub async fn new(
         worker_event_tx: mpsc::Sender<WorkerEvent>,
         controller_lock: Arc<Mutex<Option<FileLock>>>,
+        local_uid: UID,
     ) -> Result<Self> {
         info!("Loading service configurations from {}", CONFIG_DIR);
 
@@ -56,6 +172,9 @@ impl ControllerRegistry {
         // Start the global worker listener
         worker_manager.start_listener().await?;
 
+        let policy = AuthPolicy::load(Path::new(POLICY_FILE)).await?;
+        let (status_tx, _) = tokio::sync::broadcast::channel(STATUS_CHANNEL_CAPACITY);
+
         let registry = Self {
             controller_lock,
             pms: None,
@@ -63,6 +182,13 @@ impl ControllerRegistry {
             worker_manager,
             service_spawning_allowed: Arc::new(Mutex::new(false)),
             pending_autostart_services: Arc::new(Mutex::new(None)),
+            local_uid,
+            policy: Arc::new(policy),
+            activation_tasks: Arc::new(Mutex::new(HashMap::new())),
+            condition_tasks: Arc::new(Mutex::new(HashMap::new())),
+            service_logs: Arc::new(Mutex::new(HashMap::new())),
+            status_tx,
+            notify_channels: Arc::new(Mutex::new(HashMap::new())),
         };
 
         Ok(registry)
@@ -80,7 +206,6 @@ impl ControllerRegistry {
         func(service)
     }
 
-    #[allow(dead_code)]
     async fn with_service_mut<F, R>(&self, name: &str, func: F) -> Result<R>
     where
         F: FnOnce(&mut Service) -> Result<R>,
@@ -184,6 +309,9 @@ impl ControllerRegistry {
     }
 
     pub async fn load_from_disk(&mut self) -> Result<()> {
+        // A reinstalled package may have moved since the last resolution
+        package_resolver::invalidate_cache().await;
+
         let mut load_count = 0;
 
         let mut directory = fs::read_dir(CONFIG_DIR).await?;
@@ -274,7 +402,14 @@ impl ControllerRegistry {
         self.pms = Some(Box::new(pms));
     }
 
+    /// Re-parses `name`'s unit file (plus any override) and, if anything changed, admits the new
+    /// config. The running worker is only restarted when a field that actually affects the
+    /// spawned process (`command`, which carries the UID, or `se_info`) changed; other changes
+    /// (e.g. restart policy, nice name, dependencies) are picked up in place.
     pub async fn service_reload(&mut self, name: String) -> Result<Option<ServiceConfig>> {
+        // A reinstalled package may have moved since the last resolution
+        package_resolver::invalidate_cache().await;
+
         let existing_config = self
             .with_service(&name, |service| Ok(service.config().clone()))
             .await?;
@@ -283,24 +418,47 @@ impl ControllerRegistry {
             .load_unit_config(&existing_config.unit_file_path)
             .await?;
 
-        if new_config != existing_config {
-            let enabled = self.is_enabled(&name).await?;
-            self.insert_unit(new_config.clone(), enabled).await?;
-            if enabled {
-                self.service_restart(name).await?;
-            }
+        if new_config == existing_config {
+            return Ok(None);
+        }
 
-            Ok(Some(new_config))
-        } else {
-            Ok(None)
+        let enabled = self.is_enabled(&name).await?;
+        let previous_state = self
+            .with_service(&name, |service| Ok(service.state().clone()))
+            .await?;
+        let process_affecting_change = new_config.command != existing_config.command
+            || new_config.se_info != existing_config.se_info;
+
+        self.insert_unit(new_config.clone(), enabled).await?;
+
+        if enabled && process_affecting_change {
+            self.service_restart(name.clone()).await?;
+        } else if matches!(previous_state, ServiceRunState::Running { .. }) {
+            // insert_unit always admits the new config as Stopped; since we didn't actually
+            // touch the running process, restore the state that was really true.
+            self.update_service_state(name, previous_state).await?;
         }
+
+        Ok(Some(new_config))
     }
 
     pub async fn process_remote_command(
         &mut self,
         command: CLICommand,
+        actor: UID,
         shutdown_token: CancellationToken,
     ) -> CLIResponse {
+        let (action, service) = command_action(&command);
+        if !self.policy.is_allowed(&actor, service, action) {
+            warn!("Denied {actor:?} attempting {action:?} ({service:?})");
+            return CLIResponse::Error(format!(
+                "Permission denied: {actor:?} may not {action:?}{}",
+                service
+                    .map(|name| format!(" \"{name}\""))
+                    .unwrap_or_default()
+            ));
+        }
+
         match command {
             CLICommand::Start(name) => match self.service_start(name.clone(), false).await {
                 Ok(did_start) => {
@@ -371,6 +529,33 @@ impl ControllerRegistry {
                 shutdown_token.cancel();
                 CLIResponse::ShuttingDown // Respond immediately
             }
+            CLICommand::ZygoteReady => match self.handle_zygote_ready().await {
+                Ok(_) => CLIResponse::Success("Zygote ready acknowledged".into()),
+                Err(err) => CLIResponse::Error(format!("Failed to handle Zygote ready: {err}")),
+            },
+            CLICommand::Workers => CLIResponse::Workers(self.workers_summary().await),
+            CLICommand::PauseWorker(identity) => match self.pause_worker(identity.clone()).await {
+                Ok(()) => CLIResponse::Success(format!("Worker {identity:?} paused")),
+                Err(err) => CLIResponse::Error(format!("Failed to pause worker {identity:?}: {err}")),
+            },
+            CLICommand::ResumeWorker(identity) => match self.resume_worker(identity.clone()).await {
+                Ok(()) => CLIResponse::Success(format!("Worker {identity:?} resumed")),
+                Err(err) => {
+                    CLIResponse::Error(format!("Failed to resume worker {identity:?}: {err}"))
+                }
+            },
+            CLICommand::DrainWorker(identity) => match self.drain_worker(identity.clone()).await {
+                Ok(()) => CLIResponse::Success(format!("Worker {identity:?} draining")),
+                Err(err) => {
+                    CLIResponse::Error(format!("Failed to drain worker {identity:?}: {err}"))
+                }
+            },
+            CLICommand::Logs { .. } | CLICommand::Watch { .. } => {
+                unreachable!(
+                    "Logs and Watch are intercepted and handled directly in handle_command \
+                     before process_remote_command is ever called"
+                )
+            }
         }
     }
 
@@ -378,13 +563,25 @@ impl ControllerRegistry {
         let mut services = self.services.lock().await;
         if let Some(service) = services.get_mut(&name) {
             info!("Updating service state {name} with {state:?}");
-            service.set_state(state);
+            let old_state = service.state().clone();
+            service.set_state(state.clone());
+            let enabled = service.enabled();
+
+            // No subscribers is the common case (nobody's watching); ignore the error
+            let _ = self.status_tx.send(ServiceStatusDelta {
+                name,
+                old_state: Some(old_state),
+                new_state: state,
+                enabled,
+            });
         }
         Ok(())
     }
 
     async fn load_unit_config(&self, path: &Path) -> Result<ServiceConfig> {
-        ServiceConfig::parse(path).await
+        let mut config = ServiceConfig::parse(path, self.local_uid.clone()).await?;
+        apply_override(&mut config, path).await?;
+        Ok(config)
     }
 
     async fn clean_orphaned_symlinks(&self) -> Result<()> {
@@ -457,26 +654,70 @@ impl ControllerRegistry {
         self.service_start_with_id(name, id, wait_for_start).await
     }
 
+    /// Stops `name`, first recursively stopping any currently-running service that depends on it
+    /// (`wants`/`requires`/`after`) so a dependency is never pulled out from under a dependent.
     pub async fn service_stop(&mut self, name: String) -> Result<()> {
-        let config = self
-            .with_service(&name, |service| Ok(service.config().clone()))
-            .await?;
+        let mut visited = HashSet::new();
+        self.service_stop_with_dependents(name, &mut visited).await
+    }
 
-        let identity: WorkerIdentity = config.into();
-        match self.worker_manager.get_worker_for_identity(&identity).await {
-            Ok(connection) => {
-                connection
-                    .write_command(WorkerCommand::KillProcess {
-                        service_name: name.clone(),
-                    })
+    fn service_stop_with_dependents<'a>(
+        &'a mut self,
+        name: String,
+        visited: &'a mut HashSet<String>,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            if !visited.insert(name.clone()) {
+                return Ok(());
+            }
+
+            for dependent in self.running_dependents_of(&name).await? {
+                self.service_stop_with_dependents(dependent, visited)
                     .await?;
             }
-            Err(err) => error!("Cannot connect to worker to stop service \"{name}\": {err}"),
-        }
 
-        self.pms_stop(name).await;
+            let config = self
+                .with_service(&name, |service| Ok(service.config().clone()))
+                .await?;
 
-        Ok(())
+            // Mark this a deliberate stop before the kill goes out, so the worker's resulting
+            // ProcessExited/ProcessCrashed event is recognized as expected rather than handed to
+            // the restart machinery in `handle_unexpected_exit`
+            self.update_service_state(name.clone(), ServiceRunState::Stopping)
+                .await?;
+
+            let identity: WorkerIdentity = config.into();
+            match self.worker_manager.get_worker_for_identity(&identity).await {
+                Ok(connection) => {
+                    connection
+                        .write_command(WorkerCommand::KillProcess {
+                            service_name: name.clone(),
+                        })
+                        .await?;
+                }
+                Err(err) => error!("Cannot connect to worker to stop service \"{name}\": {err}"),
+            }
+
+            self.pms_stop(name).await;
+
+            Ok(())
+        })
+    }
+
+    /// Currently-running registered services whose `wants`/`requires`/`after` reference `name`
+    async fn running_dependents_of(&self, name: &str) -> Result<Vec<String>> {
+        let services = self.services.lock().await;
+        Ok(services
+            .values()
+            .filter(|service| matches!(service.state(), ServiceRunState::Running { .. }))
+            .filter(|service| {
+                let deps = &service.config().dependencies;
+                deps.wants.iter().any(|dep| dep == name)
+                    || deps.requires.iter().any(|dep| dep == name)
+                    || deps.after.iter().any(|dep| dep == name)
+            })
+            .map(|service| service.config().name.clone())
+            .collect())
     }
 
     pub async fn service_restart(&mut self, name: String) -> Result<()> {
@@ -489,6 +730,301 @@ impl ControllerRegistry {
         Ok(())
     }
 
+    /// React to a service exiting without having been asked to stop. Consults the service's
+    /// `RestartPolicy` and, if a restart is warranted, schedules one after an exponential backoff
+    /// (`min(base * 2^n, cap)`). If `restart_max` restarts have already occurred within
+    /// `restart_window_secs`, the service is transitioned to a terminal `Failed` state instead.
+    ///
+    /// If the service was last put into `ServiceRunState::Stopping` (i.e. `service_stop` asked
+    /// for this exit), it's an expected stop rather than a crash: the service is simply marked
+    /// `Stopped` and the restart policy is never consulted, mirroring `LocalRegistry`'s
+    /// `expected_stop` handling in `stop_and_should_restart`.
+    pub async fn handle_unexpected_exit(&mut self, name: String, exit_reason: String) -> Result<()> {
+        let expected_stop = self
+            .with_service(&name, |service| {
+                Ok(matches!(service.state(), ServiceRunState::Stopping))
+            })
+            .await
+            .unwrap_or(false);
+
+        if expected_stop {
+            self.update_service_state(name, ServiceRunState::Stopped)
+                .await?;
+            return Ok(());
+        }
+
+        let (policy, restart_max, restart_window, restart_delay, restart_delay_max) = self
+            .with_service(&name, |service| {
+                Ok((
+                    service.config().restart.clone(),
+                    service.config().restart_max,
+                    Duration::from_secs(service.config().restart_window_secs),
+                    Duration::from_millis(service.config().restart_delay_ms),
+                    Duration::from_millis(service.config().restart_delay_max_ms),
+                ))
+            })
+            .await?;
+
+        let wants_restart = match policy {
+            RestartPolicy::Always => true,
+            RestartPolicy::OnFailure => exit_reason != "0",
+            RestartPolicy::None => false,
+        };
+
+        if !wants_restart {
+            self.update_service_state(name, ServiceRunState::Stopped)
+                .await?;
+            return Ok(());
+        }
+
+        let restart_count = self
+            .with_service_mut(&name, |service| {
+                Ok(service.restart_count_in_window(restart_window))
+            })
+            .await?;
+
+        if restart_count >= restart_max as usize {
+            warn!(
+                "Service \"{name}\" exceeded {restart_max} restarts within {}s, giving up",
+                restart_window.as_secs()
+            );
+            self.update_service_state(
+                name,
+                ServiceRunState::Failed {
+                    reason: format!(
+                        "Exceeded {restart_max} restarts within {}s",
+                        restart_window.as_secs()
+                    ),
+                },
+            )
+            .await?;
+            return Ok(());
+        }
+
+        let delay = self
+            .with_service_mut(&name, |service| {
+                Ok(service.record_restart_and_next_backoff(restart_delay, restart_delay_max))
+            })
+            .await?;
+
+        self.update_service_state(name.clone(), ServiceRunState::Stopped)
+            .await?;
+
+        info!(
+            "Service \"{name}\" exited unexpectedly ({exit_reason}), restarting in {:.1}s",
+            delay.as_secs_f32()
+        );
+
+        let mut registry = self.clone();
+        tokio::spawn(async move {
+            sleep(delay).await;
+            if let Err(err) = registry.service_start_internal(name.clone(), false).await {
+                error!("Failed to restart service \"{name}\" after backoff: {err}");
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Handles a worker reporting a spawned process's pid: transitions the service to `Running`,
+    /// or, for a `ReadinessProbe::Notify` service, to the intermediate `Activating` state instead
+    /// (it isn't considered up until it sends `READY=1`), then kicks off whatever readiness watch
+    /// its probe needs.
+    pub async fn handle_process_attached(&mut self, name: String, pid: u32) -> Result<()> {
+        let readiness = self
+            .with_service(&name, |service| Ok(service.config().readiness.clone()))
+            .await?;
+
+        let state = if matches!(readiness, ReadinessProbe::Notify { .. }) {
+            ServiceRunState::Activating { pid: Some(pid) }
+        } else {
+            ServiceRunState::Running { pid: Some(pid) }
+        };
+        self.update_service_state(name.clone(), state).await?;
+
+        self.start_readiness_watch(name);
+        Ok(())
+    }
+
+    /// Routes a `PMSFromRemoteCommand::Notify` message to `name`'s in-progress `run_notify_watch`,
+    /// if any. A message with no matching entry (the watch already finished, or this service
+    /// isn't `Notify`-configured) is silently dropped rather than treated as an error - a stray
+    /// notification after readiness/watchdog handling has already moved on is harmless.
+    pub async fn handle_notify(&self, name: &str, event: NotifyEvent) {
+        if let Some(sender) = self.notify_channels.lock().await.get(name) {
+            let _ = sender.send(event);
+        }
+    }
+
+    /// Starts a background readiness check for `name` after its worker reported attachment. A
+    /// service with `ReadinessProbe::None` is already considered ready (the common case, and the
+    /// prior behavior), so this is a no-op unless a probe is configured.
+    pub fn start_readiness_watch(&self, name: String) {
+        let probe_config = self.clone();
+        tokio::spawn(async move {
+            let mut registry = probe_config;
+            if let Err(err) = registry.run_readiness_watch(&name).await {
+                error!("Readiness watch for \"{name}\" failed: {err}");
+            }
+        });
+    }
+
+    async fn run_readiness_watch(&mut self, name: &str) -> Result<()> {
+        let (probe, timeout_secs) = match self
+            .with_service(name, |service| Ok(service.config().readiness.clone()))
+            .await?
+        {
+            ReadinessProbe::None => return Ok(()),
+            ReadinessProbe::FileExists { path, timeout_secs } => {
+                (ReadinessCheck::FileExists(path), timeout_secs)
+            }
+            ReadinessProbe::CommandExitZero {
+                command,
+                timeout_secs,
+            } => (ReadinessCheck::CommandExitZero(command), timeout_secs),
+            ReadinessProbe::Notify {
+                timeout_secs,
+                watchdog_secs,
+            } => return self.run_notify_watch(name, timeout_secs, watchdog_secs).await,
+        };
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout_secs);
+
+        loop {
+            let still_running = self
+                .with_service(name, |service| {
+                    Ok(matches!(service.state(), ServiceRunState::Running { .. }))
+                })
+                .await
+                .unwrap_or(false);
+            if !still_running {
+                // Stopped (or already failed) out from under us; nothing left to confirm.
+                return Ok(());
+            }
+
+            if probe.passes().await {
+                info!("Readiness probe passed for \"{name}\"");
+                return Ok(());
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                warn!("Readiness probe for \"{name}\" did not pass within {timeout_secs}s");
+                return self
+                    .handle_unexpected_exit(name.to_string(), "readiness probe timed out".into())
+                    .await;
+            }
+
+            sleep(READINESS_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Registers `name`'s notify channel for the duration of `run_notify_watch_inner`, so
+    /// `handle_notify` has somewhere to deliver incoming `NotifyEvent`s while the watch is live,
+    /// then unregisters it once the watch ends for any reason.
+    async fn run_notify_watch(
+        &mut self,
+        name: &str,
+        timeout_secs: u64,
+        watchdog_secs: Option<u64>,
+    ) -> Result<()> {
+        let (notify_tx, notify_rx) = mpsc::unbounded_channel();
+        self.notify_channels
+            .lock()
+            .await
+            .insert(name.to_string(), notify_tx);
+
+        let result = self
+            .run_notify_watch_inner(name, timeout_secs, watchdog_secs, notify_rx)
+            .await;
+
+        self.notify_channels.lock().await.remove(name);
+        result
+    }
+
+    /// Waits for `name` to send `READY=1` within `timeout_secs`, then, if `watchdog_secs` is set,
+    /// keeps watching for `WATCHDOG=1` at least that often for as long as the service stays
+    /// `Running`. A missed readiness or watchdog deadline is handed to `handle_unexpected_exit`
+    /// just like a poll-based probe timing out.
+    async fn run_notify_watch_inner(
+        &mut self,
+        name: &str,
+        timeout_secs: u64,
+        watchdog_secs: Option<u64>,
+        mut notify_rx: mpsc::UnboundedReceiver<NotifyEvent>,
+    ) -> Result<()> {
+        loop {
+            let still_activating = self
+                .with_service(name, |service| {
+                    Ok(matches!(service.state(), ServiceRunState::Activating { .. }))
+                })
+                .await
+                .unwrap_or(false);
+            if !still_activating {
+                // Stopped (or already failed) out from under us; nothing left to confirm.
+                return Ok(());
+            }
+
+            match timeout(Duration::from_secs(timeout_secs), notify_rx.recv()).await {
+                Ok(Some(NotifyEvent::Ready)) => break,
+                Ok(Some(NotifyEvent::Watchdog)) => continue,
+                Ok(None) => return Ok(()),
+                Err(_) => {
+                    warn!("Notify readiness for \"{name}\" did not arrive within {timeout_secs}s");
+                    return self
+                        .handle_unexpected_exit(name.to_string(), "notify readiness timed out".into())
+                        .await;
+                }
+            }
+        }
+
+        let pid = self
+            .with_service(name, |service| {
+                Ok(match service.state() {
+                    ServiceRunState::Activating { pid } => *pid,
+                    _ => None,
+                })
+            })
+            .await
+            .unwrap_or(None);
+        info!("Notify readiness passed for \"{name}\"");
+        self.update_service_state(name.to_string(), ServiceRunState::Running { pid })
+            .await?;
+
+        let Some(watchdog_secs) = watchdog_secs else {
+            return Ok(());
+        };
+
+        loop {
+            let still_running = self
+                .with_service(name, |service| {
+                    Ok(matches!(service.state(), ServiceRunState::Running { .. }))
+                })
+                .await
+                .unwrap_or(false);
+            if !still_running {
+                return Ok(());
+            }
+
+            match timeout(Duration::from_secs(watchdog_secs), notify_rx.recv()).await {
+                Ok(Some(_)) => continue,
+                Ok(None) => return Ok(()),
+                Err(_) => {
+                    warn!("Service \"{name}\" missed its watchdog deadline ({watchdog_secs}s)");
+                    self.update_service_state(
+                        name.to_string(),
+                        ServiceRunState::WatchdogFailed {
+                            reason: format!("missed watchdog deadline ({watchdog_secs}s)"),
+                        },
+                    )
+                    .await?;
+                    return self
+                        .handle_unexpected_exit(name.to_string(), "watchdog deadline missed".into())
+                        .await;
+                }
+            }
+        }
+    }
+
     /// Set up worker processes, restoring existing ones if available. Returns true if this is a post-exploit controller
     pub async fn setup_workers(&self) -> Result<bool> {
         self.worker_manager.wait_for_worker_reconnections().await?;
@@ -546,6 +1082,278 @@ impl ControllerRegistry {
         }
     }
 
+    /// Starts the periodic worker liveness sweep: probes every connected worker with `Ping`,
+    /// then reconciles any that failed to respond. Runs for the lifetime of the controller.
+    pub fn start_worker_liveness_monitor(&self) {
+        let registry = self.clone();
+        tokio::spawn(async move {
+            loop {
+                sleep(WORKER_LIVENESS_POLL_INTERVAL).await;
+                registry.worker_manager.ping_all().await;
+                let mut registry = registry.clone();
+                if let Err(err) = registry.reconcile_dead_workers().await {
+                    error!("Failed to reconcile dead workers: {err}");
+                }
+            }
+        });
+    }
+
+    /// Finds every `Running` service whose worker is no longer healthy and feeds it through the
+    /// same unexpected-exit/restart machinery used for a worker-reported process crash.
+    async fn reconcile_dead_workers(&mut self) -> Result<()> {
+        let dead: Vec<String> = {
+            let services = self.services.lock().await;
+            let mut dead = Vec::new();
+            for service in services.values() {
+                if !matches!(service.state(), ServiceRunState::Running { .. }) {
+                    continue;
+                }
+                let identity: WorkerIdentity = service.config().clone().into();
+                if self
+                    .worker_manager
+                    .get_worker_for_identity(&identity)
+                    .await
+                    .is_err()
+                {
+                    dead.push(service.config().name.clone());
+                }
+            }
+            dead
+        };
+
+        for name in dead {
+            warn!("Worker for service \"{name}\" is unreachable, treating as an unexpected exit");
+            self.handle_unexpected_exit(name, "worker unavailable".into())
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Builds a `WorkerSummary` for every connected worker, classifying liveness from the
+    /// connection's health state and whether it currently hosts any `Running` service.
+    pub async fn workers_summary(&self) -> Vec<WorkerSummary> {
+        let mut hosted: HashMap<WorkerIdentity, Vec<String>> = HashMap::new();
+        {
+            let services = self.services.lock().await;
+            for service in services.values() {
+                if matches!(service.state(), ServiceRunState::Running { .. }) {
+                    let identity: WorkerIdentity = service.config().clone().into();
+                    hosted
+                        .entry(identity)
+                        .or_default()
+                        .push(service.config().name.clone());
+                }
+            }
+        }
+
+        let mut summaries = Vec::new();
+        for worker in self.worker_manager.all_workers().await {
+            let identity = WorkerIdentity::new(worker.uid().clone(), Some(worker.se_info().clone()));
+            let services = hosted.remove(&identity).unwrap_or_default();
+
+            let liveness = if !worker.is_healthy().await {
+                WorkerLiveness::Dead
+            } else if services.is_empty() {
+                WorkerLiveness::Idle
+            } else {
+                WorkerLiveness::Active
+            };
+
+            summaries.push(WorkerSummary {
+                identity,
+                pid: worker.pid(),
+                liveness,
+                last_seen_secs_ago: worker.last_seen_secs_ago().await,
+                services,
+            });
+        }
+
+        summaries
+    }
+
+    /// Stops `identity`'s worker from accepting new `SpawnProcess` commands, without affecting
+    /// what it's already hosting
+    pub async fn pause_worker(&self, identity: WorkerIdentity) -> Result<()> {
+        self.worker_manager.pause_worker(&identity).await
+    }
+
+    /// Undoes a prior `pause_worker`/`drain_worker` for `identity`
+    pub async fn resume_worker(&self, identity: WorkerIdentity) -> Result<()> {
+        self.worker_manager.resume_worker(&identity).await
+    }
+
+    /// Like `pause_worker`, but `identity`'s worker also shuts itself down once it finishes
+    /// whatever it's currently hosting
+    pub async fn drain_worker(&self, identity: WorkerIdentity) -> Result<()> {
+        self.worker_manager.drain_worker(&identity).await
+    }
+
+    /// Appends a line captured from `name`'s stdout/stderr to its bounded ring buffer (waking any
+    /// `Logs { follow: true }` subscribers), then forwards it on to the service's configured
+    /// `LogSink`, if any. `is_stderr` picks the logcat priority (stderr -> warn, stdout -> info).
+    pub async fn append_log_line(&self, name: &str, line: String, is_stderr: bool) {
+        self.service_logs
+            .lock()
+            .await
+            .entry(name.to_string())
+            .or_insert_with(ServiceLogBuffer::new)
+            .push(line.clone());
+
+        let logging = self
+            .with_service(name, |service| Ok(service.config().logging.clone()))
+            .await;
+        let (logging, tag) = match logging {
+            Ok(LogSink::Null) | Err(_) => return,
+            Ok(logging) => {
+                let tag = self
+                    .with_service(name, |service| {
+                        Ok(service
+                            .config()
+                            .nice_name
+                            .clone()
+                            .unwrap_or_else(|| name.to_string()))
+                    })
+                    .await
+                    .unwrap_or_else(|_| name.to_string());
+                (logging, tag)
+            }
+        };
+
+        let priority = if is_stderr { 'w' } else { 'i' };
+        match logging {
+            LogSink::Null => {}
+            LogSink::Logcat => {
+                if let Err(err) = write_logcat_line(&tag, priority, &line).await {
+                    warn!("Failed to write log line for \"{name}\" to logcat: {err}");
+                }
+            }
+            LogSink::File(path) => {
+                if let Err(err) = append_log_line_to_file(&path, &line).await {
+                    warn!(
+                        "Failed to write log line for \"{name}\" to {}: {err}",
+                        path.display()
+                    );
+                }
+            }
+        }
+    }
+
+    /// Serves a `Logs` request directly on `stream`: replays up to `lines` buffered entries for
+    /// `name` as `CLIResponse::LogChunk`s, then, if `follow` is set, keeps streaming new lines as
+    /// they arrive until the client disconnects. Always finishes with a `CLIResponse::Success`
+    /// terminator (or a `CLIResponse::Error` if `actor` isn't authorized).
+    pub async fn stream_logs<T>(
+        &self,
+        stream: &mut T,
+        actor: &UID,
+        name: String,
+        follow: bool,
+        lines: usize,
+    ) -> Result<()>
+    where
+        T: tokio::io::AsyncWrite + Unpin + Send,
+    {
+        if !self.policy.is_allowed(actor, Some(&name), Action::Logs) {
+            warn!("Denied {actor:?} attempting Logs (\"{name}\")");
+            CLIResponse::Error(format!(
+                "Permission denied: {actor:?} may not Logs \"{name}\""
+            ))
+            .write(stream)
+            .await?;
+            return Ok(());
+        }
+
+        let (tail, mut live_rx) = {
+            let mut service_logs = self.service_logs.lock().await;
+            let buffer = service_logs
+                .entry(name.clone())
+                .or_insert_with(ServiceLogBuffer::new);
+            let live_rx = follow.then(|| buffer.live_tx.subscribe());
+            (buffer.tail(lines), live_rx)
+        };
+
+        for line in tail {
+            CLIResponse::LogChunk(line).write(stream).await?;
+        }
+
+        if let Some(live_rx) = &mut live_rx {
+            loop {
+                match live_rx.recv().await {
+                    Ok(line) => {
+                        if CLIResponse::LogChunk(line).write(stream).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        }
+
+        CLIResponse::Success(format!("End of logs for \"{name}\""))
+            .write(stream)
+            .await?;
+        Ok(())
+    }
+
+    /// Subscribes to the status event bus, receiving every subsequent `ServiceStatusDelta`
+    /// published by `update_service_state` (including `ProcessAttached` pid updates routed
+    /// through it). Intended for external monitors; a lagging subscriber just misses older
+    /// deltas rather than stalling the daemon.
+    pub fn subscribe_status(&self) -> tokio::sync::broadcast::Receiver<ServiceStatusDelta> {
+        self.status_tx.subscribe()
+    }
+
+    /// Serves a `Watch` request directly on `stream`, streaming `ServiceStatusDelta`s as
+    /// `CLIResponse::StatusChange` until the client disconnects. If `name` is set, deltas for
+    /// other services are filtered out. Always finishes with a `CLIResponse::Success` terminator
+    /// (or a `CLIResponse::Error` if `actor` isn't authorized).
+    pub async fn stream_status<T>(
+        &self,
+        stream: &mut T,
+        actor: &UID,
+        name: Option<String>,
+    ) -> Result<()>
+    where
+        T: tokio::io::AsyncWrite + Unpin + Send,
+    {
+        if !self.policy.is_allowed(actor, name.as_deref(), Action::Status) {
+            warn!("Denied {actor:?} attempting Watch ({name:?})");
+            CLIResponse::Error(format!(
+                "Permission denied: {actor:?} may not Watch{}",
+                name.as_deref()
+                    .map(|name| format!(" \"{name}\""))
+                    .unwrap_or_default()
+            ))
+            .write(stream)
+            .await?;
+            return Ok(());
+        }
+
+        let mut status_rx = self.subscribe_status();
+
+        loop {
+            match status_rx.recv().await {
+                Ok(delta) => {
+                    if name.as_deref().is_some_and(|name| name != delta.name) {
+                        continue;
+                    }
+                    if CLIResponse::StatusChange(delta).write(stream).await.is_err() {
+                        break;
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+
+        CLIResponse::Success("End of status stream".into())
+            .write(stream)
+            .await?;
+        Ok(())
+    }
+
     pub async fn send_cgroup_reparent_command(&self, pid: usize) -> Result<()> {
         info!("Sending CGroupReparentCommand for PID {pid} to system worker");
 
@@ -593,14 +1401,53 @@ impl ControllerRegistry {
                 "Executing queued autostart for {} services in dependency order",
                 pending_services.len()
             );
-            for service_name in pending_services {
+
+            let requires_by_name: HashMap<String, Vec<String>> = {
+                let services = self.services.lock().await;
+                services
+                    .values()
+                    .map(|service| {
+                        (
+                            service.config().name.clone(),
+                            service.config().dependencies.requires.clone(),
+                        )
+                    })
+                    .collect()
+            };
+
+            let mut started = Vec::new();
+            for (index, service_name) in pending_services.iter().enumerate() {
                 info!("Starting queued service \"{service_name}\"");
                 if let Err(err) = self
                     .service_start_internal(service_name.clone(), true)
                     .await
                 {
+                    let remaining = &pending_services[index + 1..];
+                    let is_hard_dependency = remaining.iter().any(|other| {
+                        requires_by_name
+                            .get(other)
+                            .map(|requires| requires.iter().any(|dep| dep == service_name))
+                            .unwrap_or(false)
+                    });
+
+                    if is_hard_dependency {
+                        error!(
+                            "Required autostart dependency \"{service_name}\" failed ({err}); rolling back {} already-started services",
+                            started.len()
+                        );
+                        for rollback_name in started.drain(..).rev() {
+                            if let Err(stop_err) = self.service_stop(rollback_name.clone()).await {
+                                warn!("Failed to roll back \"{rollback_name}\": {stop_err}");
+                            }
+                        }
+                        break;
+                    }
+
                     error!("Failed to start queued service \"{service_name}\": {err}");
+                    continue;
                 }
+
+                started.push(service_name.clone());
             }
             info!("Queued autostart sequence complete.");
         }
@@ -665,6 +1512,375 @@ impl ControllerRegistry {
         });
     }
 
+    /// Watches `CONFIG_DIR` and `ENABLED_DIR` for changes and reacts without requiring a manual
+    /// `Reload`/`ReloadAll`. Raw filesystem events are debounced per-path (~2s) so editors
+    /// that write through a temp file and rename don't trigger repeated reloads.
+    pub fn start_config_watcher(&self) {
+        let registry = self.clone();
+        tokio::spawn(async move {
+            if let Err(err) = registry.run_config_watcher().await {
+                error!("Config watcher exited unexpectedly: {err}");
+            }
+        });
+    }
+
+    async fn run_config_watcher(&self) -> Result<()> {
+        use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+        // Wide enough to ride out editor rename-dances (write-to-temp-then-rename) and partial
+        // writes of larger unit files without reacting to a half-written config.
+        const DEBOUNCE: Duration = Duration::from_secs(2);
+
+        let (event_tx, mut event_rx) = mpsc::channel(100);
+        let mut watcher = RecommendedWatcher::new(
+            move |result: notify::Result<notify::Event>| {
+                if let Ok(event) = result {
+                    let _ = event_tx.blocking_send(event);
+                }
+            },
+            notify::Config::default(),
+        )?;
+
+        watcher.watch(Path::new(CONFIG_DIR), RecursiveMode::NonRecursive)?;
+        watcher.watch(Path::new(ENABLED_DIR), RecursiveMode::NonRecursive)?;
+
+        info!("Watching {CONFIG_DIR} and {ENABLED_DIR} for changes");
+
+        let mut pending: HashMap<PathBuf, tokio::time::Instant> = HashMap::new();
+        loop {
+            let next_due = pending.values().min().copied();
+
+            tokio::select! {
+                event = event_rx.recv() => {
+                    let Some(event) = event else {
+                        return Err(Error::Unknown("Config watcher channel closed".into()));
+                    };
+                    for path in event.paths {
+                        if path.extension().map_or(false, |ext| ext == "unit") {
+                            pending.insert(path, tokio::time::Instant::now() + DEBOUNCE);
+                        }
+                    }
+                }
+                _ = sleep_until_or_pending(next_due) => {
+                    let now = tokio::time::Instant::now();
+                    let due: Vec<PathBuf> = pending
+                        .iter()
+                        .filter(|(_, fire_at)| **fire_at <= now)
+                        .map(|(path, _)| path.clone())
+                        .collect();
+
+                    for path in due {
+                        pending.remove(&path);
+                        self.clone().handle_watched_path_change(path).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Handles a debounced-settled change under `CONFIG_DIR`/`ENABLED_DIR`. An enabled-file marker
+    /// shares its filename with the unit file it enables (see `create_enabled_file`), so it's
+    /// translated via `verify_enabled_file` rather than matched directly against
+    /// `unit_file_path`. An edit to an override file is redirected to its base unit file so both
+    /// funnel through the same create/modify/remove handling below.
+    async fn handle_watched_path_change(&self, path: PathBuf) {
+        if path.starts_with(ENABLED_DIR) {
+            match self.verify_enabled_file(&path).await {
+                Some(name) => {
+                    let is_enabled = path.exists();
+                    if let Err(err) = self
+                        .with_service_mut(&name, |service| {
+                            service.set_enabled(is_enabled);
+                            Ok(())
+                        })
+                        .await
+                    {
+                        warn!("Failed to sync enabled state for \"{name}\" after {path:?} changed: {err}");
+                    } else {
+                        info!(
+                            "Service \"{name}\" {} via enabled-file change: {path:?}",
+                            if is_enabled { "enabled" } else { "disabled" }
+                        );
+                    }
+                }
+                None => warn!("Enabled-file change at {path:?} does not match any known unit file"),
+            }
+            return;
+        }
+
+        let path = base_unit_path_for(&path);
+
+        let existing_name = {
+            let services = self.services.lock().await;
+            services
+                .values()
+                .find(|service| service.config().unit_file_path == path)
+                .map(|service| service.config().name.clone())
+        };
+
+        if !path.exists() {
+            if let Some(name) = existing_name {
+                info!("Unit file removed, dropping service \"{name}\": {path:?}");
+                if let Err(err) = self.remove_unit(name).await {
+                    warn!("Failed to remove unit for deleted file {path:?}: {err}");
+                }
+            }
+            return;
+        }
+
+        match existing_name {
+            Some(name) => {
+                info!("Detected change to unit file for \"{name}\": {path:?}");
+                let mut registry = self.clone();
+                if let Err(err) = registry.service_reload(name.clone()).await {
+                    warn!("Failed to reload service \"{name}\" after file change: {err}");
+                }
+            }
+            None => {
+                info!("Detected new unit file: {path:?}");
+                match self.load_unit_config(&path).await {
+                    Ok(config) => {
+                        let name = config.name.clone();
+                        let mut registry = self.clone();
+                        if let Err(err) = registry.insert_unit(config, false).await {
+                            warn!("Failed to insert new unit \"{name}\": {err}");
+                        }
+                    }
+                    Err(err) => warn!("Failed to parse new unit file {path:?}: {err}"),
+                }
+            }
+        }
+    }
+
+    /// Starts (or restarts) the activation wait loop for every currently-registered `OnDemand`
+    /// service. Called once at startup, after `load_from_disk`.
+    pub async fn start_on_demand_activations(&self) {
+        let configs: Vec<ServiceConfig> = {
+            let services = self.services.lock().await;
+            services
+                .values()
+                .filter(|service| matches!(service.config().activation, Activation::OnDemand { .. }))
+                .map(|service| service.config().clone())
+                .collect()
+        };
+
+        for config in configs {
+            self.start_on_demand_activation(config).await;
+        }
+    }
+
+    /// Registers an activation wait task for `config`, cancelling any previous one for the same
+    /// service name (e.g. on reload).
+    async fn start_on_demand_activation(&self, config: ServiceConfig) {
+        let name = config.name.clone();
+
+        {
+            let mut tasks = self.activation_tasks.lock().await;
+            if let Some(previous) = tasks.remove(&name) {
+                previous.cancel();
+            }
+        }
+
+        if let Err(err) = self.with_service_mut(&name, |service| {
+            service.set_state(ServiceRunState::Listening);
+            Ok(())
+        }).await {
+            warn!("Failed to mark \"{name}\" as Listening: {err}");
+        }
+
+        let token = CancellationToken::new();
+        self.activation_tasks
+            .lock()
+            .await
+            .insert(name.clone(), token.clone());
+
+        let registry = self.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = token.cancelled() => {}
+                result = registry.run_on_demand_activation(config) => {
+                    if let Err(err) = result {
+                        error!("Activation loop for \"{name}\" exited unexpectedly: {err}");
+                    }
+                }
+            }
+        });
+    }
+
+    /// Stops waiting for `name`'s activation trigger, if one is registered. Used on unit removal.
+    async fn cancel_on_demand_activation(&self, name: &str) {
+        if let Some(token) = self.activation_tasks.lock().await.remove(name) {
+            token.cancel();
+        }
+    }
+
+    /// Waits for `config`'s activation trigger, starts the service, optionally idles it back
+    /// down after `idle_timeout_secs`, then loops to wait for the next trigger. This detects
+    /// demand only; the probe connection it accepts here is separate from the listener the
+    /// worker hands off to the spawned process (see `registry::spawn::bind_activation_listener`).
+    /// For a standard spawn, the worker rebinds the address and the child inherits the listening
+    /// fd (`spawn_standard`'s `dup2`/`LISTEN_FDS=1`); a Zygote spawn has no way to hand off a fd
+    /// and refuses the combination instead (see `SpawnCommand::spawn`).
+    async fn run_on_demand_activation(&self, config: ServiceConfig) -> Result<()> {
+        let Activation::OnDemand {
+            trigger,
+            idle_timeout_secs,
+        } = &config.activation
+        else {
+            return Ok(());
+        };
+
+        loop {
+            match trigger {
+                ActivationTrigger::Socket(address) => {
+                    info!(
+                        "Listening on {address} to activate \"{}\" on demand",
+                        config.name
+                    );
+                    // This bind is only used to detect the first connection; it's dropped as
+                    // soon as one arrives. The worker binds its own long-lived listener of the
+                    // same kind (see `registry::spawn::bind_activation_listener`) and hands that
+                    // one off to the spawned process, since the worker is the process that
+                    // actually execs the child and the controller/worker transport has no way to
+                    // pass a file descriptor across the TCP connection between them.
+                    if address.parse::<std::net::SocketAddr>().is_ok() {
+                        let listener = TcpListener::bind(address).await?;
+                        listener.accept().await?;
+                    } else {
+                        let path = Path::new(address);
+                        let _ = fs::remove_file(path).await;
+                        let listener = UnixListener::bind(path)?;
+                        listener.accept().await?;
+                    }
+                }
+                ActivationTrigger::Path(path) => {
+                    info!(
+                        "Watching {path:?} to activate \"{}\" on demand",
+                        config.name
+                    );
+                    while !path.exists() {
+                        sleep(WORKER_CONTROLLER_POLL_INTERVAL).await;
+                    }
+                }
+            }
+
+            while !*self.service_spawning_allowed.lock().await {
+                sleep(WORKER_CONTROLLER_POLL_INTERVAL).await;
+            }
+
+            info!("Activation trigger fired for \"{}\"", config.name);
+            if let Err(err) = self.clone().service_start(config.name.clone(), true).await {
+                warn!("Failed to start on-demand service \"{}\": {err}", config.name);
+                continue;
+            }
+
+            match idle_timeout_secs {
+                Some(idle_timeout_secs) => {
+                    sleep(Duration::from_secs(*idle_timeout_secs)).await;
+                    info!(
+                        "Idle timeout elapsed for \"{}\", stopping until next trigger",
+                        config.name
+                    );
+                    let _ = self.clone().service_stop(config.name.clone()).await;
+                    if let Err(err) = self.with_service_mut(&config.name, |service| {
+                        service.set_state(ServiceRunState::Listening);
+                        Ok(())
+                    }).await {
+                        warn!("Failed to mark \"{}\" as Listening: {err}", config.name);
+                    }
+                }
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Starts (or restarts) the condition-wait loop for every currently-registered service that
+    /// declares a `condition`. Called once at startup, after `load_from_disk`.
+    pub async fn start_condition_gated_activations(&self) {
+        let configs: Vec<ServiceConfig> = {
+            let services = self.services.lock().await;
+            services
+                .values()
+                .filter(|service| service.config().condition.is_some())
+                .map(|service| service.config().clone())
+                .collect()
+        };
+
+        for config in configs {
+            self.start_condition_gated_activation(config).await;
+        }
+    }
+
+    /// Registers a condition-wait task for `config`, cancelling any previous one for the same
+    /// service name (e.g. on reload).
+    async fn start_condition_gated_activation(&self, config: ServiceConfig) {
+        let name = config.name.clone();
+
+        {
+            let mut tasks = self.condition_tasks.lock().await;
+            if let Some(previous) = tasks.remove(&name) {
+                previous.cancel();
+            }
+        }
+
+        let token = CancellationToken::new();
+        self.condition_tasks
+            .lock()
+            .await
+            .insert(name.clone(), token.clone());
+
+        let registry = self.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = token.cancelled() => {}
+                result = registry.run_condition_gated_activation(config) => {
+                    if let Err(err) = result {
+                        error!("Condition wait loop for \"{name}\" exited unexpectedly: {err}");
+                    }
+                }
+            }
+        });
+    }
+
+    /// Stops waiting for `name`'s condition, if one is registered. Used on unit removal.
+    async fn cancel_condition_gated_activation(&self, name: &str) {
+        if let Some(token) = self.condition_tasks.lock().await.remove(name) {
+            token.cancel();
+        }
+    }
+
+    /// Polls `config`'s `condition` until it's satisfied, then starts the service once. Unlike
+    /// `run_on_demand_activation`, this doesn't re-arm afterward - a condition-gated service is
+    /// started at most once per reload, same as a plain `autostart` service would be.
+    async fn run_condition_gated_activation(&self, config: ServiceConfig) -> Result<()> {
+        let Some(condition) = &config.condition else {
+            return Ok(());
+        };
+
+        loop {
+            if condition_satisfied(condition).await {
+                break;
+            }
+            sleep(WORKER_CONTROLLER_POLL_INTERVAL).await;
+        }
+
+        while !*self.service_spawning_allowed.lock().await {
+            sleep(WORKER_CONTROLLER_POLL_INTERVAL).await;
+        }
+
+        info!("Condition satisfied for \"{}\", starting", config.name);
+        if let Err(err) = self.clone().service_start(config.name.clone(), true).await {
+            warn!(
+                "Failed to start condition-gated service \"{}\": {err}",
+                config.name
+            );
+        }
+
+        self.condition_tasks.lock().await.remove(&config.name);
+
+        Ok(())
+    }
+
     pub async fn unlock_controller_file_lock(&self) {
         let mut mutex = self.controller_lock.lock().await;
         // If we are still holding the lock, make sure to unlock before we grab a new lock
@@ -705,16 +1921,35 @@ impl ControllerRegistry {
             all_autostart_service_configs.push(config);
         }
 
+        apply_before_edges(&mut all_autostart_service_configs);
+
+        let known_names: HashSet<&str> = all_autostart_service_configs
+            .iter()
+            .map(|config| config.name.as_str())
+            .collect();
+
         // Resolve dependency graph once and extract service names in dependency order
+        let mut cycle_members = Vec::new();
         let flattened_graph = DependencyGraph::from(&all_autostart_service_configs[..])
             .filter_map(|step| match step {
                 Step::Resolved(service_config) => Some(service_config.clone()),
                 Step::Unresolved(dep_name) => {
-                    warn!("Unresolved dependency: \"{}\"", dep_name);
+                    if known_names.contains(dep_name.as_str()) {
+                        cycle_members.push(dep_name.clone());
+                    } else {
+                        warn!("Unresolved dependency: \"{}\"", dep_name);
+                    }
                     None
                 }
             })
             .collect();
+
+        if !cycle_members.is_empty() {
+            warn!(
+                "Dependency cycle detected among [{}]; excluding them from autostart",
+                cycle_members.join(", ")
+            );
+        }
         Ok(flattened_graph)
     }
 
@@ -775,14 +2010,72 @@ impl ControllerRegistry {
         id
     }
 
+    /// Starts `wants` (soft) and `requires` (hard) dependencies of `service_name`. A failing
+    /// `wants` dependency is logged and skipped; a failing `requires` dependency aborts the
+    /// start of `service_name` itself by returning an error.
     async fn start_dependencies(&mut self, service_name: &str, wait_for_start: bool) -> Result<()> {
-        let dependencies = self
+        let (wants, requires, conflicts) = self
             .with_service(service_name, |service| {
-                Ok(service.config().dependencies.wants.clone())
+                Ok((
+                    service.config().dependencies.wants.clone(),
+                    service.config().dependencies.requires.clone(),
+                    service.config().dependencies.conflicts.clone(),
+                ))
             })
             .await?;
 
-        for dep_name in dependencies {
+        for conflict_name in conflicts {
+            let is_running = self
+                .with_service(&conflict_name, |service| {
+                    Ok(matches!(service.state(), ServiceRunState::Running { .. }))
+                })
+                .await
+                .unwrap_or(false);
+
+            if is_running {
+                info!(
+                    "Stopping conflicting service \"{}\" before starting \"{}\"",
+                    conflict_name, service_name
+                );
+                self.service_stop(conflict_name).await?;
+            }
+        }
+
+        for dep_name in requires {
+            info!(
+                "Starting required dependency \"{}\" for service \"{}\"",
+                dep_name, service_name
+            );
+
+            let dep_state = self
+                .with_service(&dep_name, |service| Ok(service.state().clone()))
+                .await
+                .map_err(|_| {
+                    Error::Unknown(format!(
+                        "Required dependency \"{dep_name}\" of \"{service_name}\" does not exist"
+                    ))
+                })?;
+
+            // A dependency that already gave up crash-looping is not worth retrying as a side
+            // effect of starting something else; skip the dependent instead
+            if matches!(dep_state, ServiceRunState::Failed { .. }) {
+                return Err(Error::Unknown(format!(
+                    "Required dependency \"{dep_name}\" of \"{service_name}\" is in a Failed state; not starting \"{service_name}\""
+                )));
+            }
+
+            if !matches!(dep_state, ServiceRunState::Running { .. }) {
+                self.service_start_internal(dep_name.clone(), wait_for_start)
+                    .await
+                    .map_err(|err| {
+                        Error::Unknown(format!(
+                            "Required dependency \"{dep_name}\" of \"{service_name}\" failed to start: {err}"
+                        ))
+                    })?;
+            }
+        }
+
+        for dep_name in wants {
             info!(
                 "Starting dependency \"{}\" for service \"{}\"",
                 dep_name, service_name
@@ -820,6 +2113,39 @@ impl ControllerRegistry {
         Ok(())
     }
 
+    /// Rejects `candidate` with a `ConfigError` if adding it to the currently-registered units
+    /// would introduce a dependency cycle over the combined `wants`/`requires`/`after`/`before`
+    /// edge set.
+    async fn reject_if_cycle(&self, candidate: &ServiceConfig) -> Result<()> {
+        let mut configs: Vec<ServiceConfig> = self
+            .services
+            .lock()
+            .await
+            .values()
+            .map(|service| service.config().clone())
+            .filter(|config| config.name != candidate.name)
+            .collect();
+        configs.push(candidate.clone());
+
+        apply_before_edges(&mut configs);
+
+        let known_names: HashSet<&str> =
+            configs.iter().map(|config| config.name.as_str()).collect();
+
+        for step in DependencyGraph::from(&configs[..]) {
+            if let Step::Unresolved(dep_name) = step {
+                if known_names.contains(dep_name.as_str()) {
+                    return Err(Error::ConfigError(format!(
+                        "Unit \"{}\" introduces a dependency cycle involving \"{}\"",
+                        candidate.name, dep_name
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     async fn service_start_internal(&mut self, name: String, wait_for_start: bool) -> Result<bool> {
         let allow_start = self
             .with_service(&name, |service| {
@@ -837,6 +2163,10 @@ impl ControllerRegistry {
             return Ok(false);
         }
 
+        // Recursive dependency chains (e.g. a `requires` of a `requires`) are started through
+        // this path rather than `service_start`, so run the same preflight here.
+        self.start_dependencies(&name, wait_for_start).await?;
+
         let id = self.register_id(name.clone()).await;
         self.service_start_with_id(name, id, wait_for_start).await
     }
@@ -852,19 +2182,53 @@ impl Registry for ControllerRegistry {
         self.with_service(&name, |service| {
             Ok(service.enabled()
                 && service.config().autostart
+                && service.config().condition.is_none()
                 && *service.state() == ServiceRunState::Stopped)
         })
         .await
     }
 
     async fn insert_unit(&mut self, config: ServiceConfig, enabled: bool) -> Result<()> {
+        let declared_version = config.config_version;
+        let config = migrate_config(config)?;
+        if config.config_version != declared_version {
+            info!(
+                "Unit \"{}\" migrated from config version {} to {}",
+                config.name, declared_version, config.config_version
+            );
+            if let Err(err) = rewrite_config_version(&config.unit_file_path).await {
+                warn!(
+                    "Failed to rewrite migrated version onto \"{}\": {err}",
+                    config.unit_file_path.display()
+                );
+            }
+        }
+
+        self.reject_if_cycle(&config).await?;
+
+        let is_on_demand = matches!(config.activation, Activation::OnDemand { .. });
+        let has_condition = config.condition.is_some();
+        let config_for_activation = config.clone();
         let service = Service::new(config, ServiceRunState::Stopped, enabled);
         self.insert_service(service).await?;
+
+        if is_on_demand {
+            self.start_on_demand_activation(config_for_activation.clone())
+                .await;
+        }
+        if has_condition {
+            self.start_condition_gated_activation(config_for_activation)
+                .await;
+        }
+
         Ok(())
     }
 
     async fn remove_unit(&mut self, name: String) -> Result<bool> {
+        self.cancel_on_demand_activation(&name).await;
+        self.cancel_condition_gated_activation(&name).await;
         let _ = self.service_stop(name.clone()).await;
+        let _ = self.remove_enabled_file(&name).await;
 
         let mut services = self.services.lock().await;
         let removed = services.remove(&name).is_some();
@@ -893,6 +2257,13 @@ impl Registry for ControllerRegistry {
             )
             .await?;
 
+        if connection.is_paused().await {
+            return Err(Error::WorkerProtocolError(format!(
+                "Worker for {:?} is paused/draining, refusing to start \"{name}\"",
+                connection.uid()
+            )));
+        }
+
         let result = connection
             .write_command(WorkerCommand::SpawnProcess {
                 command,
@@ -965,8 +2336,93 @@ impl Registry for ControllerRegistry {
         Ok(services.values().map(|s| s.status()).collect())
     }
 
-    async fn shutdown(&self) -> Result<()> {
-        // Shutdown all workers using the worker manager
+    async fn shutdown(&self, _graceful: bool) -> Result<()> {
+        // Each worker's SIGTERM/SIGKILL escalation is its own `LocalRegistry`'s concern; the
+        // controller just asks every worker to shut down
         self.worker_manager.shutdown_all().await
     }
 }
+
+/// Maps a `CLICommand` to the policy `Action` that gates it and, if the command targets a
+/// specific service, that service's name.
+fn command_action(command: &CLICommand) -> (Action, Option<&str>) {
+    match command {
+        CLICommand::Start(name) => (Action::Start, Some(name.as_str())),
+        CLICommand::Stop(name) => (Action::Stop, Some(name.as_str())),
+        CLICommand::Restart(name) => (Action::Restart, Some(name.as_str())),
+        CLICommand::Enable(name) => (Action::Enable, Some(name.as_str())),
+        CLICommand::Disable(name) => (Action::Disable, Some(name.as_str())),
+        CLICommand::Reload(name) => (Action::Reload, Some(name.as_str())),
+        CLICommand::ReloadAll => (Action::ReloadAll, None),
+        CLICommand::Config(name) => (Action::Config, Some(name.as_str())),
+        CLICommand::Status(name) => (Action::Status, Some(name.as_str())),
+        CLICommand::List => (Action::List, None),
+        CLICommand::Shutdown => (Action::Shutdown, None),
+        CLICommand::ZygoteReady => (Action::ZygoteReady, None),
+        CLICommand::Workers => (Action::Workers, None),
+        CLICommand::PauseWorker(_) => (Action::ControlWorkers, None),
+        CLICommand::ResumeWorker(_) => (Action::ControlWorkers, None),
+        CLICommand::DrainWorker(_) => (Action::ControlWorkers, None),
+        CLICommand::Logs { name, .. } => (Action::Logs, Some(name.as_str())),
+        CLICommand::Watch { name } => (Action::Status, name.as_deref()),
+    }
+}
+
+/// Sleeps until `deadline` if one is pending, otherwise never resolves. Lets the config watcher's
+/// `tokio::select!` debounce loop idle without polling when there's nothing waiting to fire.
+async fn sleep_until_or_pending(deadline: Option<tokio::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Injects the other side of every `before` edge into `ordering_edges`: if service A declares
+/// `before = [B]`, then B's combined ordering edges gain A, so the topological sort places A
+/// ahead of B even though B never names A directly.
+fn apply_before_edges(configs: &mut [ServiceConfig]) {
+    let mut injected: HashMap<String, Vec<String>> = HashMap::new();
+    for config in configs.iter() {
+        for target in &config.dependencies.before {
+            injected
+                .entry(target.clone())
+                .or_default()
+                .push(config.name.clone());
+        }
+    }
+
+    for config in configs.iter_mut() {
+        if let Some(extra) = injected.remove(&config.name) {
+            config.dependencies.ordering_edges.extend(extra);
+        }
+    }
+}
+
+/// Maximum size a `LogSink::File` is allowed to grow to before being rotated to a `.1` sibling
+const LOG_FILE_ROTATE_BYTES: u64 = 1024 * 1024;
+
+/// Appends `line` to `path`, rotating the existing file to a `.1` sibling first if it has grown
+/// past `LOG_FILE_ROTATE_BYTES`
+async fn append_log_line_to_file(path: &Path, line: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    if let Ok(metadata) = fs::metadata(path).await {
+        if metadata.len() > LOG_FILE_ROTATE_BYTES {
+            let mut rotated = path.as_os_str().to_os_string();
+            rotated.push(".1");
+            fs::rename(path, rotated).await?;
+        }
+    }
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(line.as_bytes()).await?;
+    file.write_all(b"\n").await?;
+
+    Ok(())
+}
@@ -1,11 +1,12 @@
 use std::{collections::HashMap, future::ready, sync::Arc, time::Duration};
 
-use nix::libc::{SIGTERM, kill};
+use nix::libc::{SIGKILL, SIGTERM, kill};
 use pinitd_common::{
     ServiceRunState, ServiceStatus, UID,
-    unit_config::{RestartPolicy, ServiceConfig},
+    unit_config::{HealthCheckConfig, RestartPolicy, ServiceConfig},
 };
 use tokio::{
+    process::Command,
     sync::{Mutex, MutexGuard, oneshot},
     task::JoinHandle,
     time::sleep,
@@ -147,8 +148,131 @@ impl LocalRegistry {
     }
 
     pub async fn service_stop(&mut self, name: String) -> Result<()> {
-        self.with_service_mut(&name, |service| Ok(service_stop_internal(&name, service)))
+        let stop_info = self
+            .with_service_mut(&name, |service| Ok(service_stop_internal(&name, service)))
+            .await?;
+
+        if let Some(stop_info) = stop_info {
+            let registry = self.clone();
+            tokio::spawn(async move { registry.escalate_stop(name, stop_info).await });
+        }
+
+        Ok(())
+    }
+
+    /// Two-phase stop: `service_stop_internal` already sent SIGTERM and marked the service
+    /// `Stopping` before this is spawned. Waits up to `stop_info.stop_timeout`
+    /// (`ServiceConfig::stop_timeout_ms`, default 10s) for the service's watcher task to observe
+    /// the SIGTERM'd process exiting (it transitions the service out of `Stopping` itself via
+    /// `stop_and_should_restart`). If the service is still `Running`/`Stopping` when the deadline
+    /// fires, the process ignored SIGTERM, so escalate to SIGKILL
+    async fn escalate_stop(&self, name: String, stop_info: StopInfo) {
+        sleep(stop_info.stop_timeout).await;
+
+        let still_stopping = self
+            .with_service(&name, |service| {
+                Ok(matches!(
+                    service.state(),
+                    ServiceRunState::Running { .. } | ServiceRunState::Stopping
+                ))
+            })
             .await
+            .unwrap_or(false);
+
+        if !still_stopping {
+            return;
+        }
+
+        match stop_info.pid {
+            Some(pid) => {
+                warn!(
+                    "Service \"{name}\" did not exit within {}ms of SIGTERM, sending SIGKILL",
+                    stop_info.stop_timeout.as_millis()
+                );
+                let result = unsafe { kill(pid as i32, SIGKILL) };
+                if result != 0 {
+                    warn!("Failed to send SIGKILL to pid {pid}: result {result}");
+                }
+            }
+            None => warn!(
+                "Service \"{name}\" did not exit within {}ms of SIGTERM, but has no known PID to send SIGKILL to",
+                stop_info.stop_timeout.as_millis()
+            ),
+        }
+    }
+
+    /// Spawns `name`'s periodic health-check probe if it has one configured, returning the task
+    /// handle so the caller can store it alongside `monitor_task`. Returns `None` if no
+    /// `health_check` is configured
+    pub async fn start_health_check_watch(&self, name: String) -> Option<JoinHandle<()>> {
+        let health_check = self
+            .with_service(&name, |service| Ok(service.config().health_check.clone()))
+            .await
+            .ok()
+            .flatten()?;
+
+        let registry = self.clone();
+        Some(tokio::spawn(async move {
+            registry.run_health_check_watch(name, health_check).await;
+        }))
+    }
+
+    /// Runs `health_check.command` every `health_check.interval_secs` while the service stays
+    /// `Running`. Once `health_check.failure_threshold` consecutive runs exit non-zero, SIGTERMs
+    /// the pid (without marking the service `Stopping`) and returns, leaving the watcher task
+    /// spawned in `spawn` to observe the exit and hand the restart decision to
+    /// `stop_and_should_restart` as it would for any other crash
+    async fn run_health_check_watch(&self, name: String, health_check: HealthCheckConfig) {
+        let mut consecutive_failures = 0u32;
+
+        loop {
+            sleep(Duration::from_secs(health_check.interval_secs)).await;
+
+            let pid = match self
+                .with_service(&name, |service| Ok(service.state().clone()))
+                .await
+            {
+                Ok(ServiceRunState::Running { pid }) => pid,
+                _ => return,
+            };
+
+            let passed = Command::new("sh")
+                .arg("-c")
+                .arg(&health_check.command)
+                .status()
+                .await
+                .map(|status| status.success())
+                .unwrap_or(false);
+
+            if passed {
+                consecutive_failures = 0;
+                continue;
+            }
+
+            consecutive_failures += 1;
+            warn!(
+                "Health check for \"{name}\" failed ({consecutive_failures}/{} consecutive)",
+                health_check.failure_threshold
+            );
+
+            if consecutive_failures < health_check.failure_threshold {
+                continue;
+            }
+
+            warn!(
+                "Service \"{name}\" failed its health check {} times in a row, sending SIGTERM",
+                health_check.failure_threshold
+            );
+
+            if let Some(pid) = pid {
+                let result = unsafe { kill(pid as i32, SIGTERM) };
+                if result != 0 {
+                    warn!("Failed to send SIGTERM to pid {pid}: result {result}");
+                }
+            }
+
+            return;
+        }
     }
 
     pub async fn service_restart_with_id(&mut self, name: String, pinit_id: Uuid) -> Result<()> {
@@ -203,7 +327,7 @@ impl LocalRegistry {
                         expected_stop = matches!(state, ServiceRunState::Stopping);
                     }
 
-                    if !inner_registry
+                    match inner_registry
                         .stop_and_should_restart(
                             inner_name.clone(),
                             exit_code != 0,
@@ -212,12 +336,9 @@ impl LocalRegistry {
                         )
                         .await
                     {
-                        // Terminate restart loop
-                        return;
+                        Some(delay) => sleep(delay).await,
+                        None => return,
                     }
-
-                    // Otherwise restart after delay
-                    sleep(Duration::from_millis(1000)).await;
                 } else {
                     // If error, terminate loop. It has already been logged
                     return;
@@ -233,13 +354,19 @@ impl LocalRegistry {
         watcher_handle
     }
 
+    /// Applies the post-exit state transition and, if a restart is warranted, returns the
+    /// exponential-backoff delay (`min(base * 2^n, cap)`, config-driven per service via
+    /// `restart_delay_ms`/`restart_delay_max_ms`) to wait before respawning. Returns `None` when
+    /// the service should not be restarted, including when `restart_max` restarts have already
+    /// occurred within `restart_window_secs`, in which case the service is transitioned to a
+    /// terminal `Failed` state instead.
     async fn stop_and_should_restart(
         &self,
         name: String,
         did_fail: bool,
         expected_stop: bool,
         exit_message: String,
-    ) -> bool {
+    ) -> Option<Duration> {
         self.with_service_mut(&name, |service| {
             if did_fail && !expected_stop {
                 warn!(
@@ -255,25 +382,46 @@ impl LocalRegistry {
 
             if expected_stop {
                 // Do not restart
-                return Ok(false);
+                return Ok(None);
             }
 
             let should_restart = service.config().restart == RestartPolicy::Always
                 || (did_fail && service.config().restart == RestartPolicy::OnFailure);
-            if service.enabled() && should_restart {
-                warn!("Restarting service \"{name}\" due to exit: {exit_message}");
-
-                return Ok(true);
-            } else if !service.enabled() {
+            if !service.enabled() {
                 info!("Service \"{name}\" exited but is disabled, not restarting");
-            } else {
+                return Ok(None);
+            } else if !should_restart {
                 info!("Service \"{name}\" exited and restart is not configured");
+                return Ok(None);
+            }
+
+            let restart_max = service.config().restart_max;
+            let restart_window = Duration::from_secs(service.config().restart_window_secs);
+            let restart_count = service.restart_count_in_window(restart_window);
+
+            if restart_count >= restart_max as usize {
+                warn!(
+                    "Service \"{name}\" exceeded {restart_max} restarts within {}s, giving up",
+                    restart_window.as_secs()
+                );
+                service.set_state(ServiceRunState::Failed {
+                    reason: format!(
+                        "Exceeded {restart_max} restarts within {}s",
+                        restart_window.as_secs()
+                    ),
+                });
+                return Ok(None);
             }
 
-            Ok(false)
+            warn!("Restarting service \"{name}\" due to exit: {exit_message}");
+            let delay = service.record_restart_and_next_backoff(
+                Duration::from_millis(service.config().restart_delay_ms),
+                Duration::from_millis(service.config().restart_delay_max_ms),
+            );
+            Ok(Some(delay))
         })
         .await
-        .map_or(false, |b| b)
+        .unwrap_or(None)
     }
 }
 
@@ -411,18 +559,41 @@ impl Registry for LocalRegistry {
             .await
     }
 
-    async fn shutdown(&self) -> Result<()> {
-        self.with_registry_async(|mut registry| {
+    /// Sends SIGTERM to every running service. If `graceful`, waits (in parallel, bounded by each
+    /// service's own `stop_timeout_ms`) for every stop to resolve -- escalating to SIGKILL for
+    /// services that ignore SIGTERM -- before saving state, so shutdown doesn't race a still-dying
+    /// process. If not `graceful`, saves state immediately without waiting
+    async fn shutdown(&self, graceful: bool) -> Result<()> {
+        let stops = self.with_registry(|mut registry| {
+            let mut stops = Vec::new();
             for (name, service) in &mut registry.registry {
                 // We don't need to send anything at shutdown
                 let mut service = SyncedService::from(service, ControllerConnection::Disabled);
-                service_stop_internal(name, &mut service);
+                if let Some(stop_info) = service_stop_internal(name, &mut service) {
+                    stops.push((name.clone(), stop_info));
+                }
             }
-
-            // Write last known state
-            registry.stored_state.clone().save()
+            Ok(stops)
         })
-        .await
+        .await?;
+
+        if graceful {
+            let handles: Vec<_> = stops
+                .into_iter()
+                .map(|(name, stop_info)| {
+                    let registry = self.clone();
+                    tokio::spawn(async move { registry.escalate_stop(name, stop_info).await })
+                })
+                .collect();
+
+            for handle in handles {
+                let _ = handle.await;
+            }
+        }
+
+        // Write last known state
+        self.with_registry_async(|registry| registry.stored_state.clone().save())
+            .await
     }
 
     fn local_service_uid(&self) -> UID {
@@ -442,13 +613,30 @@ impl Registry for LocalRegistry {
     }
 }
 
-fn service_stop_internal(name: &str, service: &mut SyncedService) {
+/// What's needed to escalate a stop to SIGKILL if the service doesn't exit in time
+struct StopInfo {
+    pid: Option<usize>,
+    stop_timeout: Duration,
+}
+
+/// Sends SIGTERM to `service` if it's running and marks it `Stopping`. The service's own watcher
+/// task (see `spawn`) observes the process actually exiting and transitions it out of `Stopping`;
+/// callers are responsible for escalating to SIGKILL (via `escalate_stop`) if that doesn't happen
+/// within the returned `StopInfo::stop_timeout`. Returns `None` if the service wasn't running
+fn service_stop_internal(name: &str, service: &mut SyncedService) -> Option<StopInfo> {
     match &service.state() {
         ServiceRunState::Running { pid } => {
             let pid = pid.clone();
             // Transition to stopping to mark this as an intentional service stop
             service.set_state(ServiceRunState::Stopping);
 
+            // The health check has no purpose once the service is intentionally stopping, and
+            // would otherwise keep running (and could fire a spurious SIGTERM) after this point
+            if let Some(handle) = service.health_check_task() {
+                handle.abort();
+            }
+            service.set_health_check_task(None);
+
             if let Some(pid) = pid {
                 info!("Attempting to stop service \"{name}\" (PID: {pid}). Sending SIGTERM");
                 let result = unsafe { kill(pid as i32, SIGTERM) };
@@ -460,15 +648,15 @@ fn service_stop_internal(name: &str, service: &mut SyncedService) {
             } else {
                 info!("Attempting to stop service \"{name}\" with unknown PID");
             }
-            // If we have a handle, attempt to kill via handle
-            if let Some(handle) = service.monitor_task() {
-                handle.abort();
-            }
-            // Make sure handle drops
-            service.set_monitor_task(None);
+
+            Some(StopInfo {
+                pid,
+                stop_timeout: Duration::from_millis(service.config().stop_timeout_ms),
+            })
         }
         _ => {
             warn!("Service \"{name}\" is not running");
+            None
         }
     }
 }
@@ -29,7 +29,9 @@ pub trait Registry {
     async fn service_status(&self, name: String) -> Result<ServiceStatus>;
     async fn service_list_all(&self) -> Result<Vec<ServiceStatus>>;
 
-    async fn shutdown(&self) -> Result<()>;
+    /// Stops every service. If `graceful`, waits for every stop to resolve (each bounded by its
+    /// own stop timeout) before returning; otherwise returns as soon as the stop is requested
+    async fn shutdown(&self, graceful: bool) -> Result<()>;
 
     /// Returns the UID for local "standard" (non-Zygote) spawns. Either `UID::System` or `UID::Shell`
     fn local_service_uid(&self) -> UID;
@@ -1,14 +1,22 @@
-use std::{env, future, path::PathBuf, process::Stdio};
+use std::{
+    env, future,
+    os::{
+        fd::{AsRawFd, RawFd},
+        unix::net::UnixListener as StdUnixListener,
+    },
+    path::Path,
+    process::Stdio,
+};
 
 use crate::{
-    android::fetch_package_path,
     error::{Error, Result},
     exploit::exploit,
 };
 use android_31317_exploit::{ExploitKind, TriggerApp};
 use pinitd_common::{
     ServiceRunState, UID,
-    unit_config::{ServiceCommand, ServiceCommandKind, ServiceConfig},
+    package_resolver::{cached_package_path, expand_package_references, resolve_package_reference},
+    unit_config::{Activation, ActivationTrigger, ServiceCommand, ServiceCommandKind, ServiceConfig},
 };
 use tokio::process::{Child, Command};
 use uuid::Uuid;
@@ -41,15 +49,44 @@ impl SpawnCommand {
             }
         };
 
-        let child = if !force_standard_spawn
+        let activation_listener = match &config.activation {
+            Activation::OnDemand {
+                trigger: ActivationTrigger::Socket(address),
+                ..
+            } => match bind_activation_listener(address) {
+                Ok(listener) => Some(listener),
+                Err(err) => {
+                    error!("Failed to bind activation listener for \"{name}\": {err}");
+                    return Err(err);
+                }
+            },
+            _ => None,
+        };
+
+        let use_zygote = !force_standard_spawn
             && ((config.command.uid != UID::Shell && config.command.uid != UID::System)
-                || force_zygote_spawn)
-        {
+                || force_zygote_spawn);
+
+        // The Zygote exploit payload has no channel to hand a pre-opened fd to the process it
+        // launches, unlike `spawn_standard`'s dup2 onto `ACTIVATION_LISTENER_FD`. Rather than
+        // silently dropping the listener we just bound (and, for a unix-socket address, the
+        // socket path we just unlinked), refuse the combination outright.
+        if use_zygote && activation_listener.is_some() {
+            let error_msg = format!(
+                "Service \"{name}\" is socket-activated but must spawn via Zygote (uid {:?}): \
+                 inherited-fd handoff isn't supported on that path",
+                config.command.uid
+            );
+            error!("{error_msg}");
+            return Err(Error::ProcessSpawnError(error_msg));
+        }
+
+        let child = if use_zygote {
             info!("Launching \"{name}\" via Zygote");
             spawn_zygote_exploit(config, command, pinit_id).await
         } else {
             info!("Launching \"{name}\" via normal spawn");
-            spawn_standard(command, pinit_id).await
+            spawn_standard(command, pinit_id, activation_listener).await
         };
 
         match child {
@@ -62,6 +99,15 @@ impl SpawnCommand {
                     })
                     .await?;
 
+                if let Some(handle) = registry.start_health_check_watch(name.clone()).await {
+                    registry
+                        .with_service_mut(&name, |service| {
+                            service.set_health_check_task(Some(handle));
+                            Ok(())
+                        })
+                        .await?;
+                }
+
                 info!("Monitoring task started for service \"{name}\"");
                 let result = child.wait(&name).await;
                 info!("Monitoring task finished for service \"{name}\"");
@@ -136,21 +182,88 @@ impl InnerSpawnChild {
     }
 }
 
-async fn spawn_standard(command: String, pinit_id: Uuid) -> Result<InnerSpawnChild> {
+async fn spawn_standard(
+    command: String,
+    pinit_id: Uuid,
+    activation_listener: Option<ActivationListener>,
+) -> Result<InnerSpawnChild> {
     let command = wrapper_command(&command, pinit_id, false)?;
 
-    let child = Command::new("sh")
+    let mut command = Command::new("sh");
+    command
         .args(&["-c", &command])
-        // TODO: Auto pipe output to Android log?
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
+        // This outer process's own stdout/stderr carry nothing worth capturing: its logging goes
+        // through `ai_pin_logger`, not these streams, and the service's real output comes from a
+        // second, inner spawn inside `monitored-wrapper` (see `wrapper::specialize_with_monitoring`),
+        // which forwards it over the PMS connection as `LogLine`s that
+        // `ControllerRegistry::append_log_line` routes to the service's configured `LogSink`.
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
         // Make sure we clean up if we die
-        .kill_on_drop(true)
-        .spawn()?;
+        .kill_on_drop(true);
+
+    if let Some(listener) = &activation_listener {
+        // LISTEN_FDS=1 is systemd's convention for "one pre-opened socket is waiting on fd 3";
+        // we don't set LISTEN_PID since the wrapper re-execs through a second `sh -c` hop before
+        // reaching the service's own command, so there's no single pid we could put there that
+        // would still match by the time the service checks it.
+        command.env("LISTEN_FDS", "1");
+        let raw_fd = listener.as_raw_fd();
+        unsafe {
+            command.pre_exec(move || {
+                nix::unistd::dup2(raw_fd, ACTIVATION_LISTENER_FD)
+                    .map_err(|errno| std::io::Error::from_raw_os_error(errno as i32))?;
+                Ok(())
+            });
+        }
+    }
+
+    let child = command.spawn()?;
+    // The listener's original fd was duped onto `ACTIVATION_LISTENER_FD` in the child; our copy
+    // is no longer needed once the child has it
+    drop(activation_listener);
 
     Ok(InnerSpawnChild::Standard(child))
 }
 
+/// Fixed fd a service's inherited activation listener is duped onto in the child, matching
+/// systemd's `SD_LISTEN_FDS_START`
+const ACTIVATION_LISTENER_FD: RawFd = 3;
+
+/// A listening socket bound by the worker for an `ActivationTrigger::Socket` service, kept open
+/// until it's handed to the spawned child via `spawn_standard`
+enum ActivationListener {
+    Tcp(std::net::TcpListener),
+    Unix(StdUnixListener),
+}
+
+impl AsRawFd for ActivationListener {
+    fn as_raw_fd(&self) -> RawFd {
+        match self {
+            ActivationListener::Tcp(listener) => listener.as_raw_fd(),
+            ActivationListener::Unix(listener) => listener.as_raw_fd(),
+        }
+    }
+}
+
+/// Binds `address` as either a TCP or a unix-socket listener depending on whether it parses as a
+/// `SocketAddr`, for handoff to a socket-activated service's child process. This is a fresh bind
+/// of the same address the controller used only to detect demand (see
+/// `ControllerRegistry::run_on_demand_activation`) - `std`'s TCP listener already sets
+/// `SO_REUSEADDR` on unix, so rebinding here doesn't race a lingering `TIME_WAIT` from the
+/// controller's just-dropped probe connection.
+fn bind_activation_listener(address: &str) -> Result<ActivationListener> {
+    if address.parse::<std::net::SocketAddr>().is_ok() {
+        Ok(ActivationListener::Tcp(std::net::TcpListener::bind(
+            address,
+        )?))
+    } else {
+        let path = Path::new(address);
+        let _ = std::fs::remove_file(path);
+        Ok(ActivationListener::Unix(StdUnixListener::bind(path)?))
+    }
+}
+
 async fn spawn_zygote_exploit(
     config: ServiceConfig,
     command: String,
@@ -189,25 +302,23 @@ fn wrapper_command(command: &str, pinit_id: Uuid, is_zygote: bool) -> Result<Str
 
 async fn expanded_command(command: &ServiceCommand) -> Result<(String, bool)> {
     let command = match &command.kind {
-        ServiceCommandKind::Command { command, .. } => command.clone(),
+        ServiceCommandKind::Command { command, .. } => expand_package_references(command).await?,
         ServiceCommandKind::LaunchPackageBinary {
             package,
             content_path,
             args,
             ..
         } => {
-            let package_path = fetch_package_path(&package).await?;
-            let path = PathBuf::from(package_path);
-            let path = path.join(
-                content_path
-                    .strip_prefix("/")
-                    .unwrap_or_else(|| &content_path),
-            );
+            let trimmed_content_path = content_path.strip_prefix('/').unwrap_or(content_path);
+            let reference = format!("package:{package}/{trimmed_content_path}");
+            let path = resolve_package_reference(&reference).await?;
 
             let command = path.display().to_string();
 
             let command = if let Some(args) = args {
-                format!("{command} {args}").trim().to_string()
+                format!("{command} {}", expand_package_references(args).await?)
+                    .trim()
+                    .to_string()
             } else {
                 command
             };
@@ -225,7 +336,7 @@ async fn expanded_command(command: &ServiceCommand) -> Result<(String, bool)> {
             jvm_args,
             ..
         } => {
-            let package_path = fetch_package_path(&package).await?;
+            let package_path = cached_package_path(package).await?;
 
             let args = if let Some(command_args) = command_args {
                 command_args
@@ -1,11 +1,22 @@
 use pinitd_common::STATE_FILE;
 use serde::{Deserialize, Serialize};
-use tokio::fs;
+use serde_json::Value;
+use tokio::{fs, io::AsyncWriteExt};
 
 use crate::error::{Error, Result};
 
+/// Current on-disk schema version. Bump this and extend `migrate` whenever `StoredState`'s shape
+/// changes (e.g. `enabled_services` growing from bare names into per-service metadata), so
+/// existing installs upgrade in place instead of failing to load
+const CURRENT_STATE_VERSION: u32 = 1;
+
+const STATE_BACKUP_SUFFIX: &str = ".bak";
+const STATE_TMP_SUFFIX: &str = ".tmp";
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct StoredState {
+    #[serde(default)]
+    version: u32,
     pub enabled_services: Vec<String>,
     is_dummy: bool,
 }
@@ -14,6 +25,7 @@ impl StoredState {
     /// Variant of StoredState that always marks everything as enabled
     pub fn dummy() -> Self {
         Self {
+            version: CURRENT_STATE_VERSION,
             enabled_services: Vec::new(),
             is_dummy: true,
         }
@@ -21,13 +33,22 @@ impl StoredState {
 
     pub async fn load() -> Result<Self> {
         match fs::read_to_string(STATE_FILE).await {
-            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Ok(content) => match Self::parse(&content) {
+                Ok(state) => Ok(state),
+                Err(err) => {
+                    warn!(
+                        "Failed to parse {STATE_FILE} ({err}), falling back to {STATE_FILE}{STATE_BACKUP_SUFFIX}"
+                    );
+                    Self::load_backup().await
+                }
+            },
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
                 info!(
                     "State file {} not found, assuming no services are enabled.",
                     STATE_FILE
                 );
                 Ok(Self {
+                    version: CURRENT_STATE_VERSION,
                     enabled_services: Vec::new(),
                     is_dummy: false,
                 })
@@ -36,42 +57,98 @@ impl StoredState {
         }
     }
 
-    pub async fn save(self) -> Result<()> {
+    async fn load_backup() -> Result<Self> {
+        let backup_path = format!("{STATE_FILE}{STATE_BACKUP_SUFFIX}");
+        let content = fs::read_to_string(&backup_path).await.map_err(|e| {
+            Error::ConfigError(format!(
+                "Primary state file is corrupt and backup {backup_path} could not be read: {e}"
+            ))
+        })?;
+
+        Self::parse(&content)
+    }
+
+    /// Deserializes `content`, migrating it up to `CURRENT_STATE_VERSION` first if it was written
+    /// by an older version of pinitd
+    fn parse(content: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(content)?;
+        let value = Self::migrate(value);
+
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Upgrades a parsed but possibly-outdated state document to the current schema. Each future
+    /// version bump should add a branch here rather than changing what old files deserialize to
+    fn migrate(mut value: Value) -> Value {
+        let version = value.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+
+        if version < 1 {
+            // Pre-versioning installs only ever had `enabled_services`; nothing to reshape yet,
+            // just stamp a version so future migrations have a base to diff against
+        }
+
+        if let Value::Object(map) = &mut value {
+            map.insert("version".to_string(), Value::from(CURRENT_STATE_VERSION));
+        }
+
+        value
+    }
+
+    /// Atomically persists state: written to a sibling temp file, fsync'd, then renamed over
+    /// `STATE_FILE` so a crash mid-write can never leave a truncated file behind. The previous
+    /// good state is copied to `STATE_FILE.bak` first, so `load` has something to recover from if
+    /// the new content is ever found to be unreadable
+    pub async fn save(mut self) -> Result<()> {
         if self.is_dummy {
             return Ok(());
         }
 
+        self.version = CURRENT_STATE_VERSION;
         let content = serde_json::to_string_pretty(&self)?;
 
-        fs::write(STATE_FILE, content).await?;
+        let tmp_path = format!("{STATE_FILE}{STATE_TMP_SUFFIX}");
+        let mut tmp_file = fs::File::create(&tmp_path).await?;
+        tmp_file.write_all(content.as_bytes()).await?;
+        tmp_file.sync_all().await?;
+        drop(tmp_file);
+
+        if fs::metadata(STATE_FILE).await.is_ok() {
+            fs::copy(STATE_FILE, format!("{STATE_FILE}{STATE_BACKUP_SUFFIX}")).await?;
+        }
+
+        fs::rename(&tmp_path, STATE_FILE).await?;
         info!("Wrote updated state");
 
         Ok(())
     }
 
-    pub async fn enable_service(&mut self, name: String) {
+    pub async fn enable_service(&mut self, name: String) -> Result<()> {
         if self.is_dummy {
-            return;
+            return Ok(());
         }
 
         if self.enabled_services.iter().find(|s| **s == name).is_some() {
             // Service is not already enabled
             self.enabled_services.push(name);
             // Since it doesn't matter clone the state before saving for nicer async
-            self.clone().save().await;
+            self.clone().save().await?;
         }
+
+        Ok(())
     }
 
-    pub async fn disable_service(&mut self, name: String) {
+    pub async fn disable_service(&mut self, name: String) -> Result<()> {
         if self.is_dummy {
-            return;
+            return Ok(());
         }
 
         if let Some(i) = self.enabled_services.iter().position(|s| *s == name) {
             self.enabled_services.swap_remove(i);
             // Since it doesn't matter clone the state before saving for nicer async
-            self.clone().save().await;
+            self.clone().save().await?;
         }
+
+        Ok(())
     }
 
     pub fn enabled(&self, name: &String) -> bool {
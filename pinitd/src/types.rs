@@ -1,3 +1,8 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
 use pinitd_common::{ServiceRunState, ServiceStatus, unit_config::ServiceConfig};
 use serde::{Deserialize, Serialize};
 
@@ -8,14 +13,35 @@ pub struct BaseService {
     pub enabled: bool,
 }
 
+/// Tracks the exponential-backoff restart supervision state for a single service. Not part of
+/// `BaseService` as none of it needs to survive a process restart or cross the wire.
+///
+/// Modeled on systemd's `StartLimitBurst`/`StartLimitIntervalSec`: once `restart_count_in_window`
+/// reaches `ServiceConfig::restart_max` within `restart_window_secs`, the service is given up on
+/// and transitioned to `Failed` instead of restarted again. `ControllerRegistry::handle_unexpected_exit`
+/// and `LocalRegistry::stop_and_should_restart` each consult this independently - they're separate
+/// implementations of that policy rather than a single shared code path, so a fix to one doesn't
+/// carry over to the other.
+#[derive(Clone, Default)]
+struct RestartState {
+    /// Timestamps of restarts attempted within the current `restart_window`
+    restart_timestamps: VecDeque<Instant>,
+    /// Number of consecutive backoff doublings applied since the service last stayed up
+    backoff_exponent: u32,
+    /// When the service last entered `Running`, used to decide when to reset the backoff
+    running_since: Option<Instant>,
+}
+
 pub struct Service {
     inner: BaseService,
+    restart_state: RestartState,
 }
 
 impl Clone for Service {
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
+            restart_state: self.restart_state.clone(),
         }
     }
 }
@@ -28,6 +54,7 @@ impl Service {
                 state,
                 enabled,
             },
+            restart_state: RestartState::default(),
         }
     }
 
@@ -38,6 +65,20 @@ impl Service {
             enabled: self.inner.enabled,
             state: self.inner.state.clone(),
             config_path: self.inner.config.unit_file_path.clone(),
+            restart_count: {
+                let window = Duration::from_secs(self.inner.config.restart_window_secs);
+                self.restart_state
+                    .restart_timestamps
+                    .iter()
+                    .filter(|timestamp| timestamp.elapsed() <= window)
+                    .count() as u32
+            },
+            current_backoff_secs: self
+                .current_backoff(
+                    Duration::from_millis(self.inner.config.restart_delay_ms),
+                    Duration::from_millis(self.inner.config.restart_delay_max_ms),
+                )
+                .as_secs(),
         }
     }
 
@@ -49,11 +90,58 @@ impl Service {
         self.inner.enabled
     }
 
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.inner.enabled = enabled;
+    }
+
     pub fn state(&self) -> &ServiceRunState {
         &self.inner.state
     }
 
     pub fn set_state(&mut self, state: ServiceRunState) {
+        if matches!(state, ServiceRunState::Running { .. }) {
+            self.restart_state.running_since = Some(Instant::now());
+        } else {
+            self.restart_state.running_since = None;
+        }
         self.inner.state = state;
     }
+
+    /// Drops restart timestamps older than `window` and, if the service has stayed `Running`
+    /// longer than `window`, resets the backoff exponent back to zero.
+    fn prune_restart_window(&mut self, window: Duration) {
+        let now = Instant::now();
+        self.restart_state
+            .restart_timestamps
+            .retain(|timestamp| now.duration_since(*timestamp) <= window);
+
+        if let Some(running_since) = self.restart_state.running_since {
+            if now.duration_since(running_since) > window {
+                self.restart_state.backoff_exponent = 0;
+            }
+        }
+    }
+
+    /// Number of restarts recorded within `window` after pruning stale entries.
+    pub fn restart_count_in_window(&mut self, window: Duration) -> usize {
+        self.prune_restart_window(window);
+        self.restart_state.restart_timestamps.len()
+    }
+
+    /// Records a restart attempt and returns the backoff delay (`min(base * 2^n, cap)`) to wait
+    /// before performing it, incrementing `n` for next time.
+    pub fn record_restart_and_next_backoff(&mut self, base: Duration, cap: Duration) -> Duration {
+        self.restart_state.restart_timestamps.push_back(Instant::now());
+        let delay = self.current_backoff(base, cap);
+        self.restart_state.backoff_exponent = self.restart_state.backoff_exponent.saturating_add(1);
+        delay
+    }
+
+    fn current_backoff(&self, base: Duration, cap: Duration) -> Duration {
+        let multiplier = 1u32.checked_shl(self.restart_state.backoff_exponent);
+        match multiplier {
+            Some(multiplier) => (base.saturating_mul(multiplier)).min(cap),
+            None => cap,
+        }
+    }
 }
@@ -1,11 +1,12 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use ini::Ini;
 use pinitd_common::{
     UID,
     unit_config::{
-        ExploitTriggerActivity, RestartPolicy, ServiceCommand, ServiceCommandKind, ServiceConfig,
-        ServiceDependencies,
+        Activation, ActivationCondition, ActivationTrigger, CURRENT_SERVICE_CONFIG_VERSION,
+        ExploitTriggerActivity, HealthCheckConfig, LogSink, ReadinessProbe, RestartPolicy,
+        ServiceCommand, ServiceCommandKind, ServiceConfig, ServiceDependencies,
     },
 };
 use tokio::fs;
@@ -41,15 +42,62 @@ impl ParsableServiceConfig for ServiceConfig {
         let mut nice_name = None;
         let mut autostart = false;
         let mut restart = RestartPolicy::None;
+        let mut restart_max = DEFAULT_RESTART_MAX;
+        let mut restart_window_secs = DEFAULT_RESTART_WINDOW_SECS;
+        let mut restart_delay_ms = DEFAULT_RESTART_DELAY_MS;
+        let mut restart_delay_max_ms = DEFAULT_RESTART_DELAY_MAX_MS;
+        let mut stop_timeout_ms = DEFAULT_STOP_TIMEOUT_MS;
 
-        let mut dependencies = ServiceDependencies::default();
-        if let Some(unit_section) = ini.section(Some("Unit")) {
-            for (property, value) in unit_section.iter() {
-                if property == "Wants" {
-                    dependencies.wants = value.split(',').map(|s| s.trim().to_string()).collect();
+        let config_version = parse_config_version(&ini)?;
+        let dependencies = parse_dependencies_section(&ini)?;
+
+        let mut activation_trigger = None;
+        let mut idle_timeout_secs = None;
+        if let Some(activation_section) = ini.section(Some("Activation")) {
+            for (property, value) in activation_section.iter() {
+                match property {
+                    "Socket" => {
+                        if activation_trigger.is_some() {
+                            return Err(Error::ConfigError(
+                                "\"Socket\" and \"Path\" are mutually exclusive".into(),
+                            ));
+                        }
+                        activation_trigger = Some(ActivationTrigger::Socket(value.trim().into()));
+                    }
+                    "Path" => {
+                        if activation_trigger.is_some() {
+                            return Err(Error::ConfigError(
+                                "\"Socket\" and \"Path\" are mutually exclusive".into(),
+                            ));
+                        }
+                        activation_trigger =
+                            Some(ActivationTrigger::Path(PathBuf::from(value.trim())));
+                    }
+                    "IdleTimeout" => {
+                        idle_timeout_secs = Some(value.trim().parse().map_err(|_| {
+                            Error::ConfigError(format!("Invalid \"IdleTimeout\" value \"{value}\""))
+                        })?);
+                    }
+                    _ => {
+                        return Err(Error::ConfigError(format!(
+                            "Unsupported property \"{property}\" in [Activation]"
+                        )));
+                    }
                 }
             }
         }
+        let activation = match activation_trigger {
+            Some(trigger) => Activation::OnDemand {
+                trigger,
+                idle_timeout_secs,
+            },
+            None => Activation::Immediate,
+        };
+
+        let readiness = parse_readiness_section(&ini)?;
+        let health_check = parse_health_check_section(&ini)?;
+        let logging = parse_logging_section(&ini)?;
+        let condition = parse_condition_section(&ini)?;
 
         for (property, value) in service_section.iter() {
             match property {
@@ -110,6 +158,33 @@ impl ParsableServiceConfig for ServiceConfig {
                         .try_into()
                         .map_err(|err| Error::ConfigError(err))?
                 }
+                "RestartMax" => {
+                    restart_max = value.trim().parse().map_err(|_| {
+                        Error::ConfigError(format!("Invalid \"RestartMax\" value \"{value}\""))
+                    })?;
+                }
+                "RestartWindow" => {
+                    restart_window_secs = value.trim().parse().map_err(|_| {
+                        Error::ConfigError(format!("Invalid \"RestartWindow\" value \"{value}\""))
+                    })?;
+                }
+                "RestartDelay" => {
+                    restart_delay_ms = value.trim().parse().map_err(|_| {
+                        Error::ConfigError(format!("Invalid \"RestartDelay\" value \"{value}\""))
+                    })?;
+                }
+                "RestartDelayMax" => {
+                    restart_delay_max_ms = value.trim().parse().map_err(|_| {
+                        Error::ConfigError(format!(
+                            "Invalid \"RestartDelayMax\" value \"{value}\""
+                        ))
+                    })?;
+                }
+                "StopTimeout" => {
+                    stop_timeout_ms = value.trim().parse().map_err(|_| {
+                        Error::ConfigError(format!("Invalid \"StopTimeout\" value \"{value}\""))
+                    })?;
+                }
                 _ => {
                     return Err(Error::ConfigError(format!(
                         "Unsupported property \"{property}\""
@@ -250,18 +325,436 @@ impl ParsableServiceConfig for ServiceConfig {
         }
 
         Ok(Self {
+            config_version,
             name,
             command,
             se_info,
             nice_name,
             autostart,
             restart,
+            restart_max,
+            restart_window_secs,
+            restart_delay_ms,
+            restart_delay_max_ms,
+            stop_timeout_ms,
             unit_file_path: path.to_path_buf(),
             dependencies,
+            activation,
+            readiness,
+            health_check,
+            logging,
+            condition,
         })
     }
 }
 
+/// Default ceiling on automatic restarts within `DEFAULT_RESTART_WINDOW_SECS` before a
+/// crash-looping service is transitioned to `Failed` rather than retried again
+const DEFAULT_RESTART_MAX: u32 = 5;
+const DEFAULT_RESTART_WINDOW_SECS: u64 = 60;
+/// Default delay before the first automatic restart after a crash
+const DEFAULT_RESTART_DELAY_MS: u64 = 100;
+/// Default ceiling the exponential restart backoff is capped at
+const DEFAULT_RESTART_DELAY_MAX_MS: u64 = 30_000;
+/// Default time a service is given to exit after SIGTERM before it is sent SIGKILL
+const DEFAULT_STOP_TIMEOUT_MS: u64 = 10_000;
+/// Default readiness timeout when a probe is configured without an explicit `[Readiness]` `Timeout`
+const DEFAULT_READINESS_TIMEOUT_SECS: u64 = 30;
+/// Default interval between health check runs when `[HealthCheck]` doesn't set `Interval`
+const DEFAULT_HEALTH_CHECK_INTERVAL_SECS: u64 = 30;
+
+fn parse_readiness_section(ini: &Ini) -> Result<ReadinessProbe> {
+    let Some(readiness_section) = ini.section(Some("Readiness")) else {
+        return Ok(ReadinessProbe::None);
+    };
+
+    let mut file = None;
+    let mut command = None;
+    let mut notify = false;
+    let mut timeout_secs = None;
+    let mut watchdog_secs = None;
+
+    for (property, value) in readiness_section.iter() {
+        match property {
+            "File" => file = Some(PathBuf::from(value.trim())),
+            "Command" => command = Some(value.trim().to_string()),
+            "Notify" => {
+                notify = value.trim().parse().map_err(|_| {
+                    Error::ConfigError(format!("Invalid \"Notify\" value \"{value}\""))
+                })?;
+            }
+            "Timeout" => {
+                timeout_secs = Some(value.trim().parse().map_err(|_| {
+                    Error::ConfigError(format!("Invalid \"Timeout\" value \"{value}\""))
+                })?);
+            }
+            "Watchdog" => {
+                watchdog_secs = Some(value.trim().parse().map_err(|_| {
+                    Error::ConfigError(format!("Invalid \"Watchdog\" value \"{value}\""))
+                })?);
+            }
+            _ => {
+                return Err(Error::ConfigError(format!(
+                    "Unsupported property \"{property}\" in [Readiness]"
+                )));
+            }
+        }
+    }
+
+    let timeout_secs = timeout_secs.unwrap_or(DEFAULT_READINESS_TIMEOUT_SECS);
+
+    match (file, command, notify) {
+        (Some(_), Some(_), _) => Err(Error::ConfigError(
+            "\"File\" and \"Command\" are mutually exclusive in [Readiness]".into(),
+        )),
+        (Some(_), _, true) | (_, Some(_), true) => Err(Error::ConfigError(
+            "\"Notify\" is mutually exclusive with \"File\"/\"Command\" in [Readiness]".into(),
+        )),
+        (Some(path), None, false) => Ok(ReadinessProbe::FileExists { path, timeout_secs }),
+        (None, Some(command), false) => Ok(ReadinessProbe::CommandExitZero {
+            command,
+            timeout_secs,
+        }),
+        (None, None, true) => Ok(ReadinessProbe::Notify {
+            timeout_secs,
+            watchdog_secs,
+        }),
+        (None, None, false) => {
+            if watchdog_secs.is_some() {
+                return Err(Error::ConfigError(
+                    "\"Watchdog\" requires \"Notify\" in [Readiness]".into(),
+                ));
+            }
+            Ok(ReadinessProbe::None)
+        }
+    }
+}
+
+/// Default number of consecutive failures a health check must accumulate before the service is
+/// treated as failed, if `[HealthCheck]` doesn't set `FailureThreshold` explicitly
+const DEFAULT_HEALTH_CHECK_FAILURE_THRESHOLD: u32 = 3;
+
+fn parse_health_check_section(ini: &Ini) -> Result<Option<HealthCheckConfig>> {
+    let Some(health_check_section) = ini.section(Some("HealthCheck")) else {
+        return Ok(None);
+    };
+
+    let mut command = None;
+    let mut interval_secs = None;
+    let mut failure_threshold = None;
+
+    for (property, value) in health_check_section.iter() {
+        match property {
+            "Command" => command = Some(value.trim().to_string()),
+            "Interval" => {
+                interval_secs = Some(value.trim().parse().map_err(|_| {
+                    Error::ConfigError(format!("Invalid \"Interval\" value \"{value}\""))
+                })?);
+            }
+            "FailureThreshold" => {
+                failure_threshold = Some(value.trim().parse().map_err(|_| {
+                    Error::ConfigError(format!("Invalid \"FailureThreshold\" value \"{value}\""))
+                })?);
+            }
+            _ => {
+                return Err(Error::ConfigError(format!(
+                    "Unsupported property \"{property}\" in [HealthCheck]"
+                )));
+            }
+        }
+    }
+
+    let command = command.ok_or_else(|| {
+        Error::ConfigError("\"Command\" must be provided in [HealthCheck]".into())
+    })?;
+
+    Ok(Some(HealthCheckConfig {
+        command,
+        interval_secs: interval_secs.unwrap_or(DEFAULT_HEALTH_CHECK_INTERVAL_SECS),
+        failure_threshold: failure_threshold.unwrap_or(DEFAULT_HEALTH_CHECK_FAILURE_THRESHOLD),
+    }))
+}
+
+/// Reads `[Logging]`'s `Mode` (`null`/`logcat`/`file`, defaulting to `file` if only `File` is set
+/// and to `null` otherwise) and, for `file` mode, its `File` path
+fn parse_logging_section(ini: &Ini) -> Result<LogSink> {
+    let Some(logging_section) = ini.section(Some("Logging")) else {
+        return Ok(LogSink::Null);
+    };
+
+    let mut mode = None;
+    let mut file = None;
+
+    for (property, value) in logging_section.iter() {
+        match property {
+            "Mode" => mode = Some(value.trim().to_ascii_lowercase()),
+            "File" => file = Some(PathBuf::from(value.trim())),
+            _ => {
+                return Err(Error::ConfigError(format!(
+                    "Unsupported property \"{property}\" in [Logging]"
+                )));
+            }
+        }
+    }
+
+    let mode = mode.unwrap_or_else(|| if file.is_some() { "file".into() } else { "null".into() });
+
+    match mode.as_str() {
+        "null" => Ok(LogSink::Null),
+        "logcat" => Ok(LogSink::Logcat),
+        "file" => file.map(LogSink::File).ok_or_else(|| {
+            Error::ConfigError("[Logging] \"Mode\" is \"file\" but no \"File\" path was given".into())
+        }),
+        other => Err(Error::ConfigError(format!(
+            "Unsupported \"Mode\" value \"{other}\" in [Logging]"
+        ))),
+    }
+}
+
+/// Reads `[Condition]`'s `PathExists` or `Property` (mutually exclusive), gating `autostart` on
+/// the predicate instead of starting the service immediately
+fn parse_condition_section(ini: &Ini) -> Result<Option<ActivationCondition>> {
+    let Some(condition_section) = ini.section(Some("Condition")) else {
+        return Ok(None);
+    };
+
+    let mut condition = None;
+
+    for (property, value) in condition_section.iter() {
+        match property {
+            "PathExists" => {
+                if condition.is_some() {
+                    return Err(Error::ConfigError(
+                        "\"PathExists\" and \"Property\" are mutually exclusive in [Condition]"
+                            .into(),
+                    ));
+                }
+                condition = Some(ActivationCondition::PathExists(PathBuf::from(value.trim())));
+            }
+            "Property" => {
+                if condition.is_some() {
+                    return Err(Error::ConfigError(
+                        "\"PathExists\" and \"Property\" are mutually exclusive in [Condition]"
+                            .into(),
+                    ));
+                }
+                let (key, value) = value.trim().split_once('=').ok_or_else(|| {
+                    Error::ConfigError(format!(
+                        "Invalid \"Property\" value \"{value}\" in [Condition], expected \"key=value\""
+                    ))
+                })?;
+                condition = Some(ActivationCondition::Property {
+                    key: key.trim().to_string(),
+                    value: value.trim().to_string(),
+                });
+            }
+            _ => {
+                return Err(Error::ConfigError(format!(
+                    "Unsupported property \"{property}\" in [Condition]"
+                )));
+            }
+        }
+    }
+
+    Ok(condition)
+}
+
+/// Reads `[Unit]`'s `Version` property, defaulting to `1` for unit files that predate this field
+fn parse_config_version(ini: &Ini) -> Result<u32> {
+    match ini.section(Some("Unit")).and_then(|section| section.get("Version")) {
+        Some(value) => value
+            .trim()
+            .parse()
+            .map_err(|_| Error::ConfigError(format!("Invalid \"Version\" value \"{value}\" in [Unit]"))),
+        None => Ok(1),
+    }
+}
+
+/// Ordered chain of upgrade steps, one per `(declared version) -> (declared version + 1)`
+/// transition. Empty today since `CURRENT_SERVICE_CONFIG_VERSION` is still 1 — the day the
+/// schema actually changes in a way older unit files need translating for, its step goes here.
+const MIGRATIONS: &[fn(ServiceConfig) -> ServiceConfig] = &[];
+
+/// Rejects a unit file declaring a schema version newer than this build understands, and walks
+/// an older one forward through `MIGRATIONS` until it reaches `CURRENT_SERVICE_CONFIG_VERSION`.
+pub fn migrate_config(mut config: ServiceConfig) -> Result<ServiceConfig> {
+    if config.config_version > CURRENT_SERVICE_CONFIG_VERSION {
+        return Err(Error::ConfigError(format!(
+            "Unit \"{}\" declares config version {}, newer than this build supports ({})",
+            config.name, config.config_version, CURRENT_SERVICE_CONFIG_VERSION
+        )));
+    }
+
+    while (config.config_version as usize) < MIGRATIONS.len() {
+        let step = MIGRATIONS[config.config_version as usize];
+        config = step(config);
+        config.config_version += 1;
+    }
+
+    if config.config_version != CURRENT_SERVICE_CONFIG_VERSION {
+        return Err(Error::ConfigError(format!(
+            "No migration available to bring unit \"{}\" from config version {} to {}",
+            config.name, config.config_version, CURRENT_SERVICE_CONFIG_VERSION
+        )));
+    }
+
+    Ok(config)
+}
+
+/// Rewrites `path`'s `[Unit]` `Version` property to `CURRENT_SERVICE_CONFIG_VERSION`, inserting
+/// it if absent. Called after a unit file is migrated forward so it doesn't keep re-triggering
+/// the same migration on every future parse.
+pub async fn rewrite_config_version(path: &Path) -> Result<()> {
+    let content = fs::read_to_string(path).await?;
+    let mut ini = Ini::load_from_str(&content)
+        .map_err(|e| Error::ConfigError(format!("INI parsing error: {e}")))?;
+
+    ini.with_section(Some("Unit"))
+        .set("Version", CURRENT_SERVICE_CONFIG_VERSION.to_string());
+
+    ini.write_to_file(path)
+        .map_err(|e| Error::ConfigError(format!("Failed to rewrite {path:?}: {e}")))?;
+
+    Ok(())
+}
+
+fn parse_dependencies_section(ini: &Ini) -> Result<ServiceDependencies> {
+    let mut wants = Vec::new();
+    let mut requires = Vec::new();
+    let mut after = Vec::new();
+    let mut before = Vec::new();
+    let mut conflicts = Vec::new();
+    if let Some(unit_section) = ini.section(Some("Unit")) {
+        for (property, value) in unit_section.iter() {
+            let names: Vec<String> = value.split(',').map(|s| s.trim().to_string()).collect();
+            match property {
+                "Wants" => wants = names,
+                "Requires" => requires = names,
+                "After" => after = names,
+                "Before" => before = names,
+                "Conflicts" => conflicts = names,
+                // Parsed separately by `parse_config_version`
+                "Version" => {}
+                _ => {
+                    return Err(Error::ConfigError(format!(
+                        "Unsupported property \"{property}\" in [Unit]"
+                    )));
+                }
+            }
+        }
+    }
+    Ok(ServiceDependencies::new(wants, requires, after, before, conflicts))
+}
+
+/// Path of the optional per-service override file for a unit file, e.g. `foo.unit` ->
+/// `foo.override.unit`. Lives alongside the base unit file so it travels with it.
+fn override_path_for(unit_file_path: &Path) -> PathBuf {
+    let stem = unit_file_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+    let dir = unit_file_path.parent().unwrap_or_else(|| Path::new("."));
+    dir.join(format!("{stem}.override.unit"))
+}
+
+/// Returns the base unit file path a watched path corresponds to: unchanged for an ordinary unit
+/// file, or the owning unit file if `path` is itself an override file (e.g. `foo.override.unit`
+/// -> `foo.unit`). Lets the config watcher funnel edits to either file through the same reload
+/// logic.
+pub fn base_unit_path_for(path: &Path) -> PathBuf {
+    if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+        if let Some(base_stem) = stem.strip_suffix(".override") {
+            let dir = path.parent().unwrap_or_else(|| Path::new("."));
+            return dir.join(format!("{base_stem}.unit"));
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Layers an optional `<name>.override.unit` file on top of an already-parsed `ServiceConfig`.
+/// Its `[Service]` section accepts `SeInfo`, `NiceName`, `Restart`, `RestartMax`,
+/// `RestartWindow`, `RestartDelay`, `RestartDelayMax` and `StopTimeout`, each replacing the
+/// corresponding base field; any other `[Service]` property is rejected. A `[Unit]` section, if
+/// present, replaces the base dependencies as a whole. This lets operators tweak a packaged unit
+/// without editing it directly. A missing override file is not an error — `config` is left
+/// untouched.
+pub async fn apply_override(config: &mut ServiceConfig, unit_file_path: &Path) -> Result<()> {
+    let override_path = override_path_for(unit_file_path);
+    if !override_path.exists() {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&override_path).await.or_else(|_| {
+        Err(Error::Unknown(format!(
+            "Failed to read override file {:?}",
+            override_path
+        )))
+    })?;
+    let ini = Ini::load_from_str(&content)
+        .map_err(|e| Error::ConfigError(format!("INI parsing error in override: {e}")))?;
+
+    if let Some(service_section) = ini.section(Some("Service")) {
+        for (property, value) in service_section.iter() {
+            match property {
+                "SeInfo" => config.se_info = Some(value.trim().into()),
+                "NiceName" => config.nice_name = Some(value.trim().into()),
+                "Restart" => {
+                    config.restart = value
+                        .trim()
+                        .try_into()
+                        .map_err(|err| Error::ConfigError(err))?
+                }
+                "RestartMax" => {
+                    config.restart_max = value.trim().parse().map_err(|_| {
+                        Error::ConfigError(format!(
+                            "Invalid \"RestartMax\" value \"{value}\" in override"
+                        ))
+                    })?;
+                }
+                "RestartWindow" => {
+                    config.restart_window_secs = value.trim().parse().map_err(|_| {
+                        Error::ConfigError(format!(
+                            "Invalid \"RestartWindow\" value \"{value}\" in override"
+                        ))
+                    })?;
+                }
+                "RestartDelay" => {
+                    config.restart_delay_ms = value.trim().parse().map_err(|_| {
+                        Error::ConfigError(format!(
+                            "Invalid \"RestartDelay\" value \"{value}\" in override"
+                        ))
+                    })?;
+                }
+                "RestartDelayMax" => {
+                    config.restart_delay_max_ms = value.trim().parse().map_err(|_| {
+                        Error::ConfigError(format!(
+                            "Invalid \"RestartDelayMax\" value \"{value}\" in override"
+                        ))
+                    })?;
+                }
+                "StopTimeout" => {
+                    config.stop_timeout_ms = value.trim().parse().map_err(|_| {
+                        Error::ConfigError(format!(
+                            "Invalid \"StopTimeout\" value \"{value}\" in override"
+                        ))
+                    })?;
+                }
+                _ => {
+                    return Err(Error::ConfigError(format!(
+                        "Unsupported property \"{property}\" in override [Service]"
+                    )));
+                }
+            }
+        }
+    }
+
+    if ini.section(Some("Unit")).is_some() {
+        config.dependencies = parse_dependencies_section(&ini)?;
+    }
+
+    Ok(())
+}
+
 fn extract_package_path(value: &str, field_name: &str) -> Result<(String, String)> {
     let mut iter = value.trim().splitn(2, "/");
     let package = iter.next();
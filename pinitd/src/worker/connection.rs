@@ -1,7 +1,15 @@
-use std::{error::Error, sync::Arc};
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    },
+};
 
 use pinitd_common::{
-    UID, WORKER_SOCKET_ADDRESS,
+    UID, WORKER_HEARTBEAT_INTERVAL, WORKER_HEARTBEAT_MISSED_THRESHOLD, WORKER_SOCKET_ADDRESS,
+    WorkerIdentity, auth,
     protocol::writable::{ProtocolRead, ProtocolWrite},
 };
 use tokio::{
@@ -11,16 +19,19 @@ use tokio::{
         tcp::{OwnedReadHalf, OwnedWriteHalf},
     },
     sync::{
-        Mutex, MutexGuard, mpsc,
+        Mutex, MutexGuard, mpsc, oneshot,
         watch::{self, Receiver},
     },
     task::JoinHandle,
-    time::{Duration, sleep, timeout},
+    time::{Duration, Instant, sleep, timeout},
 };
 
 use crate::error::Result;
 
-use super::protocol::{WorkerCommand, WorkerEvent, WorkerMessage, WorkerResponse, WorkerState};
+use super::protocol::{
+    WorkerCommand, WorkerEvent, WorkerMessage, WorkerRequest, WorkerResponse,
+    WorkerResponseEnvelope, WorkerState,
+};
 
 /// Connection held by Controller to transfer data to/from Worker
 #[derive(Clone)]
@@ -29,10 +40,59 @@ pub struct WorkerConnection {
     uid: UID,
     se_info: String,
     pid: usize,
-    read: Arc<Mutex<mpsc::Receiver<WorkerResponse>>>,
+    /// Monotonically increasing serial stamped on each outgoing `WorkerRequest`
+    serial: Arc<AtomicU32>,
+    /// Requests awaiting a response, keyed by the serial they were sent with
+    pending: Arc<Mutex<HashMap<u32, oneshot::Sender<WorkerResponse>>>>,
     _read_loop: Arc<Mutex<JoinHandle<()>>>,
+    _heartbeat_watch: Arc<Mutex<JoinHandle<()>>>,
     // When set, ignore socket errors as we're shutting down
     in_shutdown: bool,
+    /// Timestamp of the last message (response, event, or heartbeat) received from this worker,
+    /// used to classify liveness (see `last_seen_secs_ago`)
+    last_seen: Arc<Mutex<Instant>>,
+    /// Set by `pause`/`drain` and cleared by `resume`; checked before routing a new `SpawnProcess`
+    /// to this worker so the controller doesn't have to round-trip to find out it was refused
+    paused: Arc<Mutex<bool>>,
+}
+
+/// Governs how `ControllerConnection::open_with_retry` retries a lost connection to the
+/// controller
+#[derive(Debug, Clone)]
+pub enum ReconnectStrategy {
+    FixedInterval {
+        interval: Duration,
+        max_retries: Option<u32>,
+    },
+    ExponentialBackoff {
+        base: Duration,
+        factor: u32,
+        max_delay: Duration,
+        max_retries: Option<u32>,
+    },
+}
+
+impl ReconnectStrategy {
+    fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        match self {
+            ReconnectStrategy::FixedInterval { interval, .. } => *interval,
+            ReconnectStrategy::ExponentialBackoff {
+                base,
+                factor,
+                max_delay,
+                ..
+            } => base
+                .saturating_mul(factor.saturating_pow(attempt))
+                .min(*max_delay),
+        }
+    }
+
+    fn max_retries(&self) -> Option<u32> {
+        match self {
+            ReconnectStrategy::FixedInterval { max_retries, .. } => *max_retries,
+            ReconnectStrategy::ExponentialBackoff { max_retries, .. } => *max_retries,
+        }
+    }
 }
 
 /// Connection held by Worker to transfer data to/from Controller
@@ -83,8 +143,9 @@ impl Connection {
 impl WorkerConnection {
     async fn start_read_loop(
         connection: Connection,
-        read_tx: mpsc::Sender<WorkerResponse>,
+        pending: Arc<Mutex<HashMap<u32, oneshot::Sender<WorkerResponse>>>>,
         worker_event_tx: mpsc::Sender<WorkerEvent>,
+        last_seen: Arc<Mutex<Instant>>,
     ) -> JoinHandle<()> {
         tokio::spawn(async move {
             // Permanently hold read lock
@@ -93,10 +154,27 @@ impl WorkerConnection {
             loop {
                 match WorkerMessage::read(&mut *read_lock).await {
                     Ok(message) => {
+                        // Any message at all (response, event, or heartbeat) is liveness evidence
+                        *last_seen.lock().await = Instant::now();
+
                         match message {
-                            WorkerMessage::Response(response) => {
-                                // Send command responses to the response channel
-                                let _ = read_tx.send(response).await;
+                            WorkerMessage::Response(WorkerResponseEnvelope {
+                                serial,
+                                response,
+                            }) => {
+                                // Route to whoever sent the request with this serial. A missing
+                                // entry (stale/duplicate serial) is just dropped, not treated as
+                                // a connection failure.
+                                match pending.lock().await.remove(&serial) {
+                                    Some(tx) => {
+                                        let _ = tx.send(response);
+                                    }
+                                    None => {
+                                        warn!(
+                                            "Received response for unknown or already-resolved serial {serial}"
+                                        );
+                                    }
+                                }
                             }
                             WorkerMessage::Event(event) => {
                                 // Send events to the global event handler
@@ -124,19 +202,55 @@ impl WorkerConnection {
         })
     }
 
+    /// Watches `last_seen`, marking the connection disconnected if
+    /// `WORKER_HEARTBEAT_MISSED_THRESHOLD` worth of heartbeat intervals pass without any message
+    /// (response, event, or heartbeat) being observed. Catches a worker that's gone silent
+    /// without the read loop itself ever failing.
+    fn start_heartbeat_watch(
+        connection: Connection,
+        last_seen: Arc<Mutex<Instant>>,
+    ) -> JoinHandle<()> {
+        let timeout = WORKER_HEARTBEAT_INTERVAL * WORKER_HEARTBEAT_MISSED_THRESHOLD;
+
+        tokio::spawn(async move {
+            loop {
+                sleep(WORKER_HEARTBEAT_INTERVAL).await;
+
+                if !connection.is_connected() {
+                    return;
+                }
+
+                let elapsed = last_seen.lock().await.elapsed();
+                if elapsed > timeout {
+                    connection.mark_disconnected(format!(
+                        "No message received in {}s, exceeding heartbeat timeout of {}s",
+                        elapsed.as_secs(),
+                        timeout.as_secs()
+                    ));
+                    return;
+                }
+            }
+        })
+    }
+
     pub async fn write_command(&self, command: WorkerCommand) -> Result<WorkerResponse> {
+        let serial = self.serial.fetch_add(1, Ordering::SeqCst);
+        let (response_tx, response_rx) = oneshot::channel();
+        self.pending.lock().await.insert(serial, response_tx);
+
         match timeout(Duration::from_millis(200), async move {
             info!("Sending worker command");
             let mut write = self.connection.write.lock().await;
-            command.write(&mut *write).await?;
+            WorkerRequest { serial, command }.write(&mut *write).await?;
+            drop(write);
 
             if self.in_shutdown {
                 return Ok(WorkerResponse::ShuttingDown);
             }
 
-            match self.read.lock().await.recv().await {
-                Some(response) => Ok(response),
-                None => Err(crate::error::Error::WorkerProtocolError(
+            match response_rx.await {
+                Ok(response) => Ok(response),
+                Err(_) => Err(crate::error::Error::WorkerProtocolError(
                     "Connection closed".into(),
                 )),
             }
@@ -148,15 +262,18 @@ impl WorkerConnection {
                     // Convert into local error
                     Err(crate::error::Error::WorkerProtocolError(err))
                 } else {
+                    *self.last_seen.lock().await = Instant::now();
                     Ok(response)
                 }
             }
             Ok(Err(err)) => {
                 // Any error immediately closes the connection
+                self.pending.lock().await.remove(&serial);
                 self.connection.mark_disconnected(err.to_string());
                 Err(err)
             }
             Err(err) => {
+                self.pending.lock().await.remove(&serial);
                 self.connection.mark_disconnected(err.to_string());
                 Err(err.into())
             }
@@ -171,6 +288,10 @@ impl WorkerConnection {
         &self.se_info
     }
 
+    pub fn identity(&self) -> WorkerIdentity {
+        WorkerIdentity::new(self.uid.clone(), Some(self.se_info.clone()))
+    }
+
     pub fn pid(&self) -> usize {
         self.pid
     }
@@ -197,9 +318,71 @@ impl WorkerConnection {
         }
     }
 
+    /// Sends a liveness probe. A failure (including a timeout) marks the connection
+    /// disconnected via `write_command`'s existing error handling.
+    pub async fn ping(&self) -> Result<()> {
+        match self.write_command(WorkerCommand::Ping).await? {
+            WorkerResponse::Pong => Ok(()),
+            _ => Err(crate::error::Error::WorkerProtocolError(
+                "Unexpected response to Ping".into(),
+            )),
+        }
+    }
+
+    pub async fn last_seen_secs_ago(&self) -> u64 {
+        self.last_seen.lock().await.elapsed().as_secs()
+    }
+
+    /// Whether this worker is currently refusing new `SpawnProcess` commands, set by a prior
+    /// `pause`/`drain`
+    pub async fn is_paused(&self) -> bool {
+        *self.paused.lock().await
+    }
+
+    /// Stops the worker from accepting new spawns without touching what it's already hosting
+    pub async fn pause(&self) -> Result<()> {
+        match self.write_command(WorkerCommand::Pause).await? {
+            WorkerResponse::Success => {
+                *self.paused.lock().await = true;
+                Ok(())
+            }
+            _ => Err(crate::error::Error::WorkerProtocolError(
+                "Unexpected response to Pause".into(),
+            )),
+        }
+    }
+
+    /// Undoes a prior `pause`/`drain`
+    pub async fn resume(&self) -> Result<()> {
+        match self.write_command(WorkerCommand::Resume).await? {
+            WorkerResponse::Success => {
+                *self.paused.lock().await = false;
+                Ok(())
+            }
+            _ => Err(crate::error::Error::WorkerProtocolError(
+                "Unexpected response to Resume".into(),
+            )),
+        }
+    }
+
+    /// Like `pause`, but the worker also shuts itself down once its current processes have all
+    /// exited on their own (see `WorkerEvent::Drained`)
+    pub async fn drain(&self) -> Result<()> {
+        match self.write_command(WorkerCommand::Drain).await? {
+            WorkerResponse::Success => {
+                *self.paused.lock().await = true;
+                Ok(())
+            }
+            _ => Err(crate::error::Error::WorkerProtocolError(
+                "Unexpected response to Drain".into(),
+            )),
+        }
+    }
+
     pub async fn shutdown(&mut self) {
         self.in_shutdown = true;
         self._read_loop.lock().await.abort();
+        self._heartbeat_watch.lock().await.abort();
     }
 
     pub async fn from_connection(
@@ -237,18 +420,31 @@ impl WorkerConnection {
             error!("Failed to resend worker registration event: {}", e);
         }
 
-        let (read_tx, read_rx) = mpsc::channel::<WorkerResponse>(10);
-        let read_loop =
-            WorkerConnection::start_read_loop(connection.clone(), read_tx, worker_event_tx).await;
+        let last_seen = Arc::new(Mutex::new(Instant::now()));
+
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let read_loop = WorkerConnection::start_read_loop(
+            connection.clone(),
+            pending.clone(),
+            worker_event_tx,
+            last_seen.clone(),
+        )
+        .await;
+        let heartbeat_watch =
+            WorkerConnection::start_heartbeat_watch(connection.clone(), last_seen.clone());
 
         Ok(WorkerConnection {
             connection,
             uid,
             se_info,
             pid,
-            read: Arc::new(Mutex::new(read_rx)),
+            serial: Arc::new(AtomicU32::new(0)),
+            pending,
             _read_loop: Arc::new(Mutex::new(read_loop)),
+            _heartbeat_watch: Arc::new(Mutex::new(heartbeat_watch)),
             in_shutdown: false,
+            last_seen,
+            paused: Arc::new(Mutex::new(false)),
         })
     }
 
@@ -260,19 +456,51 @@ impl WorkerConnection {
 
 impl ControllerConnection {
     pub async fn open() -> Result<Self> {
-        let stream = timeout(Duration::from_secs(5), async move {
+        let mut stream = timeout(Duration::from_secs(5), async move {
             TcpStream::connect(WORKER_SOCKET_ADDRESS).await
         })
         .await??;
+
+        // Prove identity before the controller trusts anything from this connection, again on
+        // every reconnect rather than just the first time
+        let secret = auth::load_or_create_secret().await?;
+        timeout(Duration::from_secs(5), auth::answer_challenge(&mut stream, &secret)).await??;
+
         info!("Connected to controller");
 
         Ok(ControllerConnection(Connection::from(stream, false)))
     }
 
-    pub async fn read_command(&self) -> Result<WorkerCommand> {
+    /// Repeatedly calls `open` per `strategy` until it succeeds or `strategy`'s `max_retries`
+    /// (if any) is exhausted
+    pub async fn open_with_retry(strategy: &ReconnectStrategy) -> Result<Self> {
+        let mut attempt = 0;
+
+        loop {
+            match Self::open().await {
+                Ok(connection) => return Ok(connection),
+                Err(err) => {
+                    if strategy.max_retries().is_some_and(|max| attempt >= max) {
+                        return Err(err);
+                    }
+
+                    let delay = strategy.delay_for_attempt(attempt);
+                    warn!(
+                        "Failed to connect to controller (attempt {attempt}): {err}. Retrying in {delay:?}"
+                    );
+                    sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Returns the serial the controller tagged this command with, so the response can be
+    /// correlated back to the matching request on the controller's side
+    pub async fn read_command(&self) -> Result<(u32, WorkerCommand)> {
         let mut read = self.0.read.lock().await;
-        match WorkerCommand::read(&mut *read).await {
-            Ok(command) => Ok(command),
+        match WorkerRequest::read(&mut *read).await {
+            Ok(WorkerRequest { serial, command }) => Ok((serial, command)),
             Err(err) => {
                 // Any error immediately closes the connection
                 self.0.mark_disconnected(err.to_string());
@@ -8,7 +8,10 @@ use crate::{
     worker::protocol::{ServiceState, WorkerState},
     wrapper::daemonize,
 };
-use pinitd_common::{ServiceRunState, UID, WORKER_CONTROLLER_POLL_INTERVAL, WorkerIdentity};
+use pinitd_common::{
+    ServiceRunState, UID, WORKER_CONTROLLER_POLL_INTERVAL, WORKER_HEARTBEAT_INTERVAL,
+    WorkerIdentity,
+};
 use tokio::{
     process::Command,
     select,
@@ -21,11 +24,22 @@ use uuid::Uuid;
 use crate::{
     error::Result,
     worker::{
-        connection::ControllerConnection,
-        protocol::{WorkerCommand, WorkerEvent, WorkerMessage, WorkerResponse},
+        connection::{ControllerConnection, ReconnectStrategy},
+        protocol::{
+            WorkerCommand, WorkerEvent, WorkerMessage, WorkerResponse, WorkerResponseEnvelope,
+        },
     },
 };
 
+/// Keeps retrying the controller connection indefinitely, backing off up to 30s between
+/// attempts, rather than hammering a controller that's still starting up or mid-restart
+const RECONNECT_STRATEGY: ReconnectStrategy = ReconnectStrategy::ExponentialBackoff {
+    base: Duration::from_millis(500),
+    factor: 2,
+    max_delay: Duration::from_secs(30),
+    max_retries: None,
+};
+
 /// Comprehensive process tracking information
 #[allow(dead_code)]
 #[derive(Debug)]
@@ -63,12 +77,14 @@ impl WorkerProcess {
 
             let start_time = SystemTime::now();
             let running_processes = Arc::new(Mutex::new(HashMap::<String, ProcessInfo>::new()));
+            let accepting_spawns = Arc::new(Mutex::new(true));
             let worker_se_info = se_info.unwrap_or_else(|| WorkerIdentity::default_se_info(&uid));
 
             loop {
-                match ControllerConnection::open().await {
+                match ControllerConnection::open_with_retry(&RECONNECT_STRATEGY).await {
                     Ok(connection) => {
-                        // Send worker identification as first message
+                        // Re-send our identity so the controller reattaches this socket to the
+                        // same registry slot rather than treating us as a new worker
                         let worker_pid = std::process::id() as usize;
                         let identification = WorkerEvent::WorkerRegistration {
                             worker_uid: uid.clone(),
@@ -88,6 +104,7 @@ impl WorkerProcess {
                             uid.clone(),
                             start_time,
                             running_processes.clone(),
+                            accepting_spawns.clone(),
                             connection,
                         )
                         .await
@@ -107,11 +124,11 @@ impl WorkerProcess {
         uid: UID,
         start_time: SystemTime,
         running_processes: Arc<Mutex<HashMap<String, ProcessInfo>>>,
-        mut connection: ControllerConnection,
+        accepting_spawns: Arc<Mutex<bool>>,
+        connection: ControllerConnection,
     ) -> Result<()> {
         let token = CancellationToken::new();
-        // Send heartbeat every 30 seconds
-        let mut heartbeat_interval = interval(Duration::from_secs(30));
+        let mut heartbeat_interval = interval(WORKER_HEARTBEAT_INTERVAL);
 
         loop {
             select! {
@@ -134,10 +151,10 @@ impl WorkerProcess {
                     }
                 }
                 result = connection.read_command() => match result {
-                    Ok(command) => {
+                    Ok((serial, command)) => {
                         info!("Received command {command:?}");
 
-                        let response = match handle_command(command, &running_processes, &connection, &uid).await {
+                        let response = match handle_command(command, &running_processes, &accepting_spawns, &connection, &uid).await {
                             Ok(response) => response,
                             Err(err) => {
                                 let err = format!("Error processing command: {err}");
@@ -147,14 +164,16 @@ impl WorkerProcess {
                         };
 
                         info!("Sending command response");
-                        if let Err(e) = connection.write_response(WorkerMessage::Response(response)).await {
+                        let envelope = WorkerResponseEnvelope { serial, response };
+                        if let Err(e) = connection.write_response(WorkerMessage::Response(envelope)).await {
                             error!("Failed to send response: {}", e);
                         }
                     }
                     Err(err) => {
                         error!("Error processing command packet: {err}");
-                        info!("Reconnecting to controller");
-                        connection = ControllerConnection::open().await?;
+                        // Bubble up so the outer loop reconnects and re-sends our
+                        // WorkerRegistration, reattaching to the same registry slot
+                        return Err(err);
                     }
                 }
             }
@@ -250,6 +269,7 @@ impl WorkerProcess {
 async fn handle_command(
     command: WorkerCommand,
     running_processes: &Arc<Mutex<HashMap<String, ProcessInfo>>>,
+    accepting_spawns: &Arc<Mutex<bool>>,
     connection: &ControllerConnection,
     worker_uid: &UID,
 ) -> Result<WorkerResponse> {
@@ -259,6 +279,12 @@ async fn handle_command(
             pinit_id,
             service_name,
         } => {
+            if !*accepting_spawns.lock().await {
+                return Ok(WorkerResponse::Error(format!(
+                    "Worker is paused/draining, refusing to spawn '{service_name}'"
+                )));
+            }
+
             info!(
                 "Spawning process for service '{}': {}",
                 service_name, command
@@ -442,6 +468,36 @@ async fn handle_command(
             let worker_state = WorkerState { services };
             return Ok(WorkerResponse::CurrentState(worker_state));
         }
+        WorkerCommand::Ping => {
+            return Ok(WorkerResponse::Pong);
+        }
+        WorkerCommand::Pause => {
+            info!("Worker paused; refusing new spawns");
+            *accepting_spawns.lock().await = false;
+        }
+        WorkerCommand::Resume => {
+            info!("Worker resumed; accepting new spawns");
+            *accepting_spawns.lock().await = true;
+        }
+        WorkerCommand::Drain => {
+            info!("Worker draining; refusing new spawns until current processes exit");
+            *accepting_spawns.lock().await = false;
+
+            let running_processes = running_processes.clone();
+            let connection = connection.clone();
+            tokio::spawn(async move {
+                loop {
+                    if running_processes.lock().await.is_empty() {
+                        let _ = connection
+                            .write_response(WorkerMessage::Event(WorkerEvent::Drained))
+                            .await;
+                        info!("Worker drained; shutting down");
+                        std::process::exit(0);
+                    }
+                    sleep(Duration::from_millis(500)).await;
+                }
+            });
+        }
     };
 
     Ok(WorkerResponse::Success)
@@ -12,12 +12,27 @@ use uuid::Uuid;
 /// Unified message type for worker→controller communication
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
 pub enum WorkerMessage {
-    /// Response to a specific command
-    Response(WorkerResponse),
+    /// Response to a specific command, tagged with the serial of the `WorkerRequest` it answers
+    Response(WorkerResponseEnvelope),
     /// Proactive event from worker
     Event(WorkerEvent),
 }
 
+/// Controller→worker command tagged with a serial, so the controller can correlate it with its
+/// eventual response instead of assuming exactly one request is ever outstanding
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WorkerRequest {
+    pub serial: u32,
+    pub command: WorkerCommand,
+}
+
+/// A `WorkerResponse` tagged with the serial of the `WorkerRequest` it answers
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct WorkerResponseEnvelope {
+    pub serial: u32,
+    pub response: WorkerResponse,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum WorkerCommand {
     /// Spawn a process directly with the given command
@@ -36,6 +51,15 @@ pub enum WorkerCommand {
     CGroupReparentCommand { pid: usize },
     /// Request current state of all services from worker
     RequestCurrentState,
+    /// Liveness probe; expects a `Pong` response
+    Ping,
+    /// Stop accepting `SpawnProcess` while leaving any already-running processes alone
+    Pause,
+    /// Undo a prior `Pause`/`Drain`
+    Resume,
+    /// Like `Pause`, but also emits a `WorkerEvent::Drained` once every tracked process has
+    /// exited on its own, so the controller knows it's safe to shut this worker down
+    Drain,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
@@ -50,6 +74,8 @@ pub enum WorkerResponse {
     ShuttingDown,
     /// Current state of all services (response to RequestCurrentState)
     CurrentState(WorkerState),
+    /// Response to `Ping`
+    Pong,
 }
 
 /// Events that workers push to controller proactively
@@ -82,6 +108,8 @@ pub enum WorkerEvent {
         service_name: Option<String>,
         error: String,
     },
+    /// Sent once a `Drain` command's in-flight processes have all exited
+    Drained,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -101,6 +129,8 @@ impl Bincodable<'_> for WorkerEvent {}
 impl Bincodable<'_> for WorkerMessage {}
 impl Bincodable<'_> for WorkerState {}
 impl Bincodable<'_> for ServiceState {}
+impl Bincodable<'_> for WorkerRequest {}
+impl Bincodable<'_> for WorkerResponseEnvelope {}
 
 impl<T> ProtocolRead<'_, T> for WorkerCommand where T: AsyncReadExt + Unpin + Send {}
 impl<T> ProtocolRead<'_, T> for WorkerResponse where T: AsyncReadExt + Unpin + Send {}
@@ -115,3 +145,7 @@ impl<T> ProtocolRead<'_, T> for WorkerState where T: AsyncReadExt + Unpin + Send
 impl<T> ProtocolRead<'_, T> for ServiceState where T: AsyncReadExt + Unpin + Send {}
 impl<T> ProtocolWrite<'_, T> for WorkerState where T: AsyncWriteExt + Unpin + Send {}
 impl<T> ProtocolWrite<'_, T> for ServiceState where T: AsyncWriteExt + Unpin + Send {}
+impl<T> ProtocolRead<'_, T> for WorkerRequest where T: AsyncReadExt + Unpin + Send {}
+impl<T> ProtocolWrite<'_, T> for WorkerRequest where T: AsyncWriteExt + Unpin + Send {}
+impl<T> ProtocolRead<'_, T> for WorkerResponseEnvelope where T: AsyncReadExt + Unpin + Send {}
+impl<T> ProtocolWrite<'_, T> for WorkerResponseEnvelope where T: AsyncWriteExt + Unpin + Send {}
@@ -1,15 +1,29 @@
-use std::{process::Stdio, time::Duration};
+use std::{
+    path::{Path, PathBuf},
+    process::ExitStatus,
+    process::Stdio,
+    sync::Arc,
+    time::Duration,
+};
 
+use nix::libc::{SIGTERM, kill};
 use pinitd_common::{
-    PMS_SOCKET_ADDRESS,
+    NOTIFY_SOCKET_DIR, PMS_SOCKET_ADDRESS,
     protocol::{
-        PMSFromRemoteCommand, PMSToRemoteCommand,
+        NotifyEvent, PMSFromRemoteCommand, PMSToRemoteCommand,
         writable::{ProtocolRead, ProtocolWrite},
     },
 };
 use tokio::{
-    net::TcpStream,
+    fs,
+    io::{AsyncBufReadExt, BufReader},
+    net::{
+        TcpStream, UnixDatagram,
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+    },
     process::{Child, Command},
+    sync::Mutex,
+    task::JoinHandle,
     time::timeout,
 };
 use uuid::Uuid;
@@ -19,26 +33,42 @@ use crate::{
     zygote::init_zygote_with_fd,
 };
 
+#[derive(Clone)]
 pub struct Wrapper {
-    stream: Option<TcpStream>,
+    stream: Option<Arc<Mutex<OwnedWriteHalf>>>,
+}
+
+/// How `specialize_with_monitoring`'s wait loop ended
+enum ControlOutcome {
+    Exited(ExitStatus),
+    /// A `PMSToRemoteCommand::Detach` was received; the child is left running unmonitored
+    Detached,
 }
 
 impl Wrapper {
     pub async fn specialize_without_monitoring(
         command: String,
         using_zygote_spawn: bool,
+        notify_socket: Option<&Path>,
     ) -> Result<Child> {
         if using_zygote_spawn {
             init_zygote_with_fd().await;
         }
 
         info!("Spawning child \"{command}\"");
-        let child = Command::new("sh")
+        let mut command = Command::new("sh");
+        command
             .args(&["-c", &command])
-            // TODO: Auto pipe output to Android log?
+            // `specialize_with_monitoring` reads these pipes and forwards the output over the PMS
+            // connection as `LogLine`s; other callers get the pipes on the returned `Child`
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
+            .stderr(Stdio::piped());
+
+        if let Some(notify_socket) = notify_socket {
+            command.env("NOTIFY_SOCKET", notify_socket);
+        }
+
+        let child = command.spawn()?;
 
         info!("Spawned process with pid {:?}", child.id());
 
@@ -51,53 +81,209 @@ impl Wrapper {
         using_zygote_spawn: bool,
     ) -> Result<()> {
         info!("Negociating launch for id {pinit_id}");
-        let stream = match TcpStream::connect(PMS_SOCKET_ADDRESS).await {
+        let (read_half, write_half) = match TcpStream::connect(PMS_SOCKET_ADDRESS).await {
             Ok(mut stream) => {
                 negoticate_launch(&mut stream, pinit_id).await?;
-                Some(stream)
+                let (read_half, write_half) = stream.into_split();
+                (Some(read_half), Some(Arc::new(Mutex::new(write_half))))
             }
             Err(_) => {
                 warn!("Could not connect to PMS, continuing with spawn");
-                None
+                (None, None)
             }
         };
 
-        let mut wrapper = Wrapper { stream };
+        let wrapper = Wrapper { stream: write_half };
+
+        let notify_socket_path = PathBuf::from(NOTIFY_SOCKET_DIR).join(format!("{pinit_id}.sock"));
+        let notify_socket = bind_notify_socket(&notify_socket_path).await;
 
-        let child = Self::specialize_without_monitoring(command, using_zygote_spawn).await?;
+        let mut child = Self::specialize_without_monitoring(
+            command,
+            using_zygote_spawn,
+            Some(&notify_socket_path),
+        )
+        .await?;
+        let pid = child.id();
 
-        if let Some(pid) = child.id() {
+        if let Some(pid) = pid {
             let _ = wrapper
                 .write_if_connected(PMSFromRemoteCommand::ProcessAttached(pid))
                 .await;
         }
 
-        // TODO: Handle subsequent commands
-        let output = child.wait_with_output().await?;
-        info!("Process terminated with code {:?}", output.status.code());
+        let stdout = child.stdout.take().expect("child spawned with piped stdout");
+        let stderr = child.stderr.take().expect("child spawned with piped stderr");
+        let stdout_forwarder = tokio::spawn(forward_lines(stdout, wrapper.clone(), false));
+        let stderr_forwarder = tokio::spawn(forward_lines(stderr, wrapper.clone(), true));
+        let notify_forwarder: Option<JoinHandle<()>> = notify_socket
+            .map(|socket| tokio::spawn(forward_notify_events(socket, wrapper.clone())));
 
-        if !output.status.success() {
-            info!("stderr: {}", String::from_utf8_lossy(&output.stderr));
-        }
+        let outcome = match read_half {
+            Some(read_half) => run_with_control_channel(&mut child, pid, read_half).await?,
+            None => ControlOutcome::Exited(child.wait().await?),
+        };
+
+        match outcome {
+            ControlOutcome::Exited(status) => {
+                info!("Process terminated with code {:?}", status.code());
 
-        let _ = wrapper
-            .write_if_connected(PMSFromRemoteCommand::ProcessExited(output.status.code()))
-            .await;
+                // Let any already-buffered output drain before reporting the exit
+                let _ = stdout_forwarder.await;
+                let _ = stderr_forwarder.await;
+                if let Some(handle) = notify_forwarder {
+                    handle.abort();
+                }
+                let _ = fs::remove_file(&notify_socket_path).await;
+
+                let _ = wrapper
+                    .write_if_connected(PMSFromRemoteCommand::ProcessExited(status.code()))
+                    .await;
+            }
+            ControlOutcome::Detached => {
+                info!("Detaching from id {pinit_id}, leaving process running");
+            }
+        }
 
         Ok(())
     }
 
-    async fn write_if_connected(&mut self, command: PMSFromRemoteCommand) -> Result<()> {
-        if let Some(stream) = &mut self.stream {
-            Ok(command.write(stream).await?)
+    async fn write_if_connected(&self, command: PMSFromRemoteCommand) -> Result<()> {
+        if let Some(stream) = &self.stream {
+            Ok(command.write(&mut *stream.lock().await).await?)
         } else {
             Ok(())
         }
     }
 }
 
+/// Races the child's exit against incoming `PMSToRemoteCommand`s on the PMS control channel, so a
+/// monitored process spawned through the Zygote path can still be signalled, stopped, or detached
+/// by the controller rather than only ever observed
+async fn run_with_control_channel(
+    child: &mut Child,
+    pid: Option<u32>,
+    mut read_half: OwnedReadHalf,
+) -> Result<ControlOutcome> {
+    loop {
+        tokio::select! {
+            status = child.wait() => return Ok(ControlOutcome::Exited(status?)),
+            command = PMSToRemoteCommand::read(&mut read_half) => match command {
+                Ok(PMSToRemoteCommand::SendSignal(signal)) => send_signal(pid, signal),
+                Ok(PMSToRemoteCommand::Stop) => {
+                    info!("PMS requested graceful stop");
+                    send_signal(pid, SIGTERM);
+                }
+                Ok(PMSToRemoteCommand::Detach) => return Ok(ControlOutcome::Detached),
+                Ok(PMSToRemoteCommand::Kill) => {
+                    return Err(Error::ProcessSpawnError(
+                        "PMS requested wrapper kill. Dying".to_string(),
+                    ));
+                }
+                Ok(other) => warn!("Received unexpected PMS command after attach: {other:?}"),
+                Err(err) => {
+                    warn!("PMS control channel closed ({err}), continuing to monitor without it");
+                    return Ok(ControlOutcome::Exited(child.wait().await?));
+                }
+            },
+        }
+    }
+}
+
+fn send_signal(pid: Option<u32>, signal: i32) {
+    match pid {
+        Some(pid) => {
+            let result = unsafe { kill(pid as i32, signal) };
+            if result != 0 {
+                warn!("Failed to send signal {signal} to pid {pid}: result {result}");
+            }
+        }
+        None => warn!("Cannot send signal {signal}: unknown pid"),
+    }
+}
+
+/// Reads `output` line by line, forwarding each as a `PMSFromRemoteCommand::LogLine` until the
+/// pipe closes (the child exited or closed the descriptor itself)
+async fn forward_lines<R>(output: R, wrapper: Wrapper, is_stderr: bool)
+where
+    R: tokio::io::AsyncRead + Unpin + Send + 'static,
+{
+    let mut lines = BufReader::new(output).lines();
+    loop {
+        match lines.next_line().await {
+            Ok(Some(line)) => {
+                let _ = wrapper
+                    .write_if_connected(PMSFromRemoteCommand::LogLine { line, is_stderr })
+                    .await;
+            }
+            Ok(None) => return,
+            Err(err) => {
+                warn!("Error reading captured output: {err}");
+                return;
+            }
+        }
+    }
+}
+
+/// Binds a fresh `AF_UNIX` datagram socket at `path` for a `ReadinessProbe::Notify` service to
+/// send `READY=1`/`WATCHDOG=1` on, handed to the service via the `NOTIFY_SOCKET` env var.
+/// Binding is best-effort: a service that isn't `Notify`-configured never writes to it, and a
+/// failure here (e.g. missing parent directory) just leaves the service without one rather than
+/// failing the whole spawn.
+async fn bind_notify_socket(path: &Path) -> Option<UnixDatagram> {
+    if let Some(parent) = path.parent() {
+        if let Err(err) = fs::create_dir_all(parent).await {
+            warn!("Failed to create notify socket directory {}: {err}", parent.display());
+            return None;
+        }
+    }
+    let _ = fs::remove_file(path).await;
+
+    match UnixDatagram::bind(path) {
+        Ok(socket) => Some(socket),
+        Err(err) => {
+            warn!("Failed to bind notify socket at {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+/// Reads `KEY=VALUE` datagrams off `socket` for as long as it stays bound, forwarding recognized
+/// keys as `PMSFromRemoteCommand::Notify` until the caller aborts this task (the process has
+/// exited and no further notifications can be meaningful)
+async fn forward_notify_events(socket: UnixDatagram, wrapper: Wrapper) {
+    let mut buf = [0u8; 4096];
+    loop {
+        match socket.recv(&mut buf).await {
+            Ok(len) => {
+                let message = String::from_utf8_lossy(&buf[..len]);
+                for line in message.lines() {
+                    let event = match line.trim() {
+                        "READY=1" => Some(NotifyEvent::Ready),
+                        "WATCHDOG=1" => Some(NotifyEvent::Watchdog),
+                        _ => None,
+                    };
+                    if let Some(event) = event {
+                        let _ = wrapper
+                            .write_if_connected(PMSFromRemoteCommand::Notify(event))
+                            .await;
+                    }
+                }
+            }
+            Err(err) => {
+                warn!("Error reading notify socket: {err}");
+                return;
+            }
+        }
+    }
+}
+
 async fn negoticate_launch(stream: &mut TcpStream, pinit_id: Uuid) -> Result<()> {
     timeout(Duration::from_secs(2), async move {
+        // Answer the PMS's challenge before it will accept anything else from us
+        let secret = pinitd_common::auth::load_or_create_secret().await?;
+        pinitd_common::auth::answer_challenge(stream, &secret).await?;
+
         PMSFromRemoteCommand::WrapperLaunched(pinit_id)
             .write(stream)
             .await?;